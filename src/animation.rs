@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+
+/// Slides a card from `from` to `to` in its parent's space, `delay` seconds after it's inserted.
+/// The card is hidden while it waits, cards sweeping off the table are despawned once they arrive.
+#[derive(Component)]
+pub struct Tween {
+    pub from: Transform,
+    pub to: Transform,
+    pub delay: f32,
+    pub duration: f32,
+    pub elapsed: f32,
+    pub despawn: bool,
+}
+
+impl Tween {
+    // `duration` and `delay` are at 1x speed, there's no tween with animations off
+    pub fn new(
+        from: Transform,
+        to: Transform,
+        duration: f32,
+        delay: f32,
+        settings: &Settings,
+    ) -> Option<Self> {
+        // a hand edited settings file could have anything in it
+        let speed = settings.animation_speed.max(0.1);
+        (settings.animations && from != to).then(|| Self {
+            from,
+            to,
+            delay: delay / speed,
+            duration: duration / speed,
+            elapsed: 0.0,
+            despawn: false,
+        })
+    }
+
+    pub fn despawning(mut self) -> Self {
+        self.despawn = true;
+        self
+    }
+}
+
+// puts a card at `to`, sliding it over from `from` when there's somewhere to come from
+pub fn animate(
+    entity: &mut EntityCommands,
+    from: Option<Transform>,
+    to: Transform,
+    duration: f32,
+    delay: f32,
+    settings: &Settings,
+) {
+    match from.and_then(|from| Tween::new(from, to, duration, delay, settings)) {
+        Some(tween) if delay > 0.0 => entity.insert((tween.from, tween, Visibility::Hidden)),
+        Some(tween) => entity.insert((tween.from, tween)),
+        None => entity.insert(to),
+    };
+}
+
+// `a` in the space of a parent at `parent`
+pub fn relative_to(parent: Transform, a: Transform) -> Transform {
+    Transform::from_matrix(parent.compute_matrix().inverse() * a.compute_matrix())
+}
+
+pub fn run_tweens(
+    mut commands: Commands,
+    mut tweens: Query<(Entity, &mut Tween, &mut Transform, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (entity, mut tween, mut transform, mut visibility) in tweens.iter_mut() {
+        tween.elapsed += time.delta_secs();
+        let t = tween.elapsed - tween.delay;
+        if t < 0.0 {
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+
+        let t = (t / tween.duration).min(1.0);
+        // smoothstep, so cards ease in and out
+        let eased = t * t * (3.0 - 2.0 * t);
+        transform.translation = tween.from.translation.lerp(tween.to.translation, eased);
+        transform.rotation = tween.from.rotation.slerp(tween.to.rotation, eased);
+        transform.scale = tween.from.scale.lerp(tween.to.scale, eased);
+
+        if t == 1.0 {
+            if tween.despawn {
+                commands.entity(entity).despawn();
+            } else {
+                commands.entity(entity).remove::<Tween>();
+            }
+        }
+    }
+}
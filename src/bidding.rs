@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use crate::{button, components::*, layout::Layout, undo::UndoHistory};
+
+// the current player calls, unless it's the one call the dealer can't make
+pub fn make_call(
+    In(called): In<i32>,
+    mut game_info: ResMut<GameInfo>,
+    mut history: ResMut<UndoHistory>,
+) {
+    let seat = game_info.current_player();
+    if !game_info.calling()
+        || game_info.picking_trump
+        || !(0..=game_info.cards_per_player() as i32).contains(&called)
+        || game_info.forbidden_call(seat) == Some(called)
+    {
+        return;
+    }
+    history.push(&game_info);
+    game_info.call(called);
+}
+
+// the calls panel for a human whose turn it is to call, rebuilt after every call
+pub fn update_bidding_panel(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    layout: Res<Layout>,
+    panel: Query<Entity, With<BiddingPanel>>,
+) {
+    for entity in panel {
+        commands.entity(entity).despawn();
+    }
+
+    if !game_info.calling() || game_info.picking_trump || !viewer.has_turn(&game_info) {
+        return;
+    }
+    let seat = game_info.current_player();
+    let player = &game_info.players[seat];
+
+    let count = game_info.cards_per_player() as i32;
+    let forbidden = game_info.forbidden_call(seat);
+
+    let mut calls = vec![];
+    let mut total = 0;
+    for i in 1..=4 {
+        let other = (game_info.dealer + i) % 4;
+        if other != seat && game_info.has_called(other) {
+            let other = &game_info.players[other];
+            calls.push(format!("{} {}", other.name, other.called));
+            total += other.called;
+        }
+    }
+    let mut text = format!(
+        "{}, how many tricks do you call?\nCalls so far: {}\nTotal: {total} of {count}",
+        player.name,
+        if calls.is_empty() {
+            "none".to_string()
+        } else {
+            calls.join(", ")
+        }
+    );
+    if let Some(forbidden) = forbidden {
+        text += &format!("\nThe dealer can't call {forbidden}");
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                bottom: Val::Px(layout.card_size().y + 60.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            Pickable::IGNORE,
+            BiddingPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(text),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Pickable::IGNORE,
+            ));
+            panel
+                .spawn((
+                    Node {
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    Pickable::IGNORE,
+                ))
+                .with_children(|buttons| {
+                    for called in 0..=count {
+                        if Some(called) == forbidden {
+                            buttons.spawn((
+                                Node {
+                                    padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    ..Default::default()
+                                },
+                                BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+                                children![(
+                                    Text::new(called.to_string()),
+                                    TextColor(Color::srgb(0.4, 0.4, 0.4)),
+                                )],
+                            ));
+                            continue;
+                        }
+
+                        buttons.spawn(button(&called.to_string())).observe(
+                            move |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                commands.run_system_cached_with(make_call, called);
+                            },
+                        );
+                    }
+                });
+        });
+}
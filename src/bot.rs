@@ -0,0 +1,240 @@
+/*
+    A simple bot to fill empty seats.
+    It calls a trick for every joker, ace and high trump it holds, and plays to make its call:
+    while it still needs tricks it takes the trick as cheaply as it can,
+    once it has enough it throws the strongest card that doesn't take it.
+    Jokers are played for the trick or against it the same way.
+    When it picks the trump it goes for its strongest suit of the three cards it sees.
+*/
+
+use bevy::prelude::*;
+
+use crate::{
+    card::{Card, JokerCall, Rank, Suit, trick_winner},
+    components::*,
+    consts::BOT_DELAY,
+    spawn_table,
+    undo::UndoHistory,
+};
+
+pub fn bot_call(game_info: &GameInfo, seat: usize) -> i32 {
+    let trump = game_info.trump;
+    let cards = &game_info.players[seat].cards;
+
+    let mut strength = 0.0f32;
+    for card in cards {
+        strength += match card.rank {
+            Rank::Joker => 1.0,
+            _ if Some(card.suit) == trump && card.rank as u8 >= Rank::Jack as u8 => 1.0,
+            _ if Some(card.suit) == trump => 0.5,
+            Rank::Ace => 0.8,
+            Rank::King => 0.3,
+            _ => 0.0,
+        };
+    }
+
+    let called = strength.round() as i32;
+    let called = called.min(cards.len() as i32);
+    match game_info.forbidden_call(seat) {
+        Some(forbidden) if forbidden == called => {
+            if called > 0 {
+                called - 1
+            } else {
+                called + 1
+            }
+        }
+        _ => called,
+    }
+}
+
+// the suit with the most and highest of the first three cards, with nothing much no trump at all
+pub fn bot_trump(cards: &[Card]) -> Option<Suit> {
+    Suit::ALL
+        .into_iter()
+        .map(|suit| {
+            let ranks = cards.iter().filter(|card| card.suit == suit);
+            (ranks.map(|card| card.rank as u8 + 1).sum::<u8>(), suit)
+        })
+        .filter(|&(strength, _)| strength > Rank::Queen as u8)
+        .max_by_key(|&(strength, _)| strength)
+        .map(|(_, suit)| suit)
+}
+
+// a led joker asks for the highest trump to win, or for the suit the others most likely have to lose
+fn bot_joker_call(game_info: &GameInfo, seat: usize, want: bool) -> JokerCall {
+    let led = game_info.cards_placed.is_empty();
+    let cards = &game_info.players[seat].cards;
+    let held = |suit: Suit| cards.iter().filter(|card| card.suit == suit).count();
+
+    match (led, want) {
+        (false, true) => JokerCall::Take,
+        (false, false) => JokerCall::Pass,
+        (true, true) => JokerCall::Highest(
+            game_info
+                .trump
+                .unwrap_or_else(|| *Suit::ALL.iter().max_by_key(|s| held(**s)).unwrap()),
+        ),
+        (true, false) => JokerCall::TakeSuit(*Suit::ALL.iter().min_by_key(|s| held(**s)).unwrap()),
+    }
+}
+
+pub fn bot_card(game_info: &GameInfo, seat: usize) -> Option<(Card, Option<JokerCall>)> {
+    let trump = game_info.trump;
+    let player = &game_info.players[seat];
+    let want = player.taken < player.called;
+
+    let mut legal = player
+        .cards
+        .iter()
+        .copied()
+        .filter(|card| card.can_place(game_info))
+        .map(|card| {
+            let call = card
+                .is_joker()
+                .then(|| bot_joker_call(game_info, seat, want));
+            (card, call)
+        })
+        .collect::<Vec<_>>();
+    legal.sort_by_key(|(card, _)| card.value(trump));
+
+    let takes = |(card, call): &(Card, Option<JokerCall>)| {
+        let mut cards_placed = game_info.cards_placed.clone();
+        cards_placed.push_front(*card);
+        let mut joker_calls = game_info.joker_calls().to_vec();
+        joker_calls.extend(call.map(|call| (*card, call)));
+        trick_winner(&cards_placed, trump, &joker_calls).player == seat
+    };
+
+    if want {
+        legal
+            .iter()
+            .find(|play| takes(play))
+            .or(legal.first())
+            .copied()
+    } else {
+        legal
+            .iter()
+            .rev()
+            .find(|play| !takes(play))
+            .or(legal.first())
+            .copied()
+    }
+}
+
+// calls or plays for whichever bot's turn it is, a moment after the last move so people can follow
+pub fn bot_play(
+    mut commands: Commands,
+    mut game_info: ResMut<GameInfo>,
+    mut history: ResMut<UndoHistory>,
+    mut timer: Local<Option<Timer>>,
+    time: Res<Time>,
+) {
+    if game_info.players.is_empty() || game_info.cards_placed.len() == 4 {
+        return;
+    }
+    let seat = game_info.current_player();
+    if !game_info.players[seat].bot {
+        *timer = None;
+        return;
+    }
+
+    let timer = timer.get_or_insert_with(|| Timer::from_seconds(BOT_DELAY, TimerMode::Once));
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+    timer.reset();
+
+    // undo goes back over the bots' moves to the last one a person made
+    if game_info.picking_trump {
+        let trump = bot_trump(&game_info.first_cards(seat));
+        history.push(&game_info);
+        game_info.pick_trump(trump);
+        commands.run_system_cached(spawn_table);
+        return;
+    }
+
+    if game_info.calling() {
+        let called = bot_call(&game_info, seat);
+        history.push(&game_info);
+        game_info.call(called);
+        return;
+    }
+
+    let Some((card, joker_call)) = bot_card(&game_info, seat) else {
+        return;
+    };
+    history.push(&game_info);
+    game_info.place_card(card, joker_call);
+    commands.run_system_cached(spawn_table);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consts::ASSETS,
+        deck::{Deck, Seed},
+        record::RoundRecord,
+    };
+
+    #[test]
+    fn bots_follow_the_rules() {
+        for _ in 0..50 {
+            let mut game_info = GameInfo {
+                dealer: 3,
+                round: 8,
+                trump: Some(Suit::Hearts),
+                rounds: vec![RoundRecord::default()],
+                ..Default::default()
+            };
+            let hands = Deck::shuffled(&Seed::random()).deal(game_info.cards_per_player());
+            for (i, cards) in hands.into_iter().enumerate() {
+                game_info.players.push_back(Player {
+                    name: i.to_string(),
+                    cards,
+                    bot: true,
+                    ..Default::default()
+                });
+            }
+
+            while game_info.calling() {
+                let called = bot_call(&game_info, game_info.current_player());
+                game_info.call(called);
+            }
+            let total = game_info.players.iter().map(|p| p.called).sum::<i32>();
+            assert_ne!(total, game_info.cards_per_player() as i32);
+
+            while !game_info.players[0].cards.is_empty() {
+                while game_info.cards_placed.len() < 4 {
+                    let seat = game_info.current_player();
+                    let (card, joker_call) = bot_card(&game_info, seat).unwrap();
+                    assert!(card.can_place(&game_info));
+                    assert_eq!(card.is_joker(), joker_call.is_some());
+                    game_info.place_card(card, joker_call);
+                }
+                let winner = trick_winner(
+                    &game_info.cards_placed,
+                    game_info.trump,
+                    game_info.joker_calls(),
+                );
+                game_info.players[winner.player].taken += 1;
+                game_info.last_took = Some(winner.player);
+                game_info.cards_placed.clear();
+            }
+            assert!(game_info.players.iter().all(|p| p.cards.is_empty()));
+        }
+    }
+
+    #[test]
+    fn bots_pick_their_strongest_suit() {
+        let cards = |codes: [&str; 3]| {
+            codes.map(|code| {
+                assert!(ASSETS.contains(&code), "{code} isn't in the deck");
+                Card::from_string(code.to_string(), 0)
+            })
+        };
+        assert_eq!(bot_trump(&cards(["S7", "HA", "S8"])), Some(Suit::Hearts));
+        assert_eq!(bot_trump(&cards(["DJ", "D9", "CK"])), Some(Suit::Diamonds));
+        assert_eq!(bot_trump(&cards(["S7", "H7", "JR"])), None);
+    }
+}
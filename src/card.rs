@@ -1,8 +1,11 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
 
-use crate::GameInfo;
+use crate::{GameInfo, consts::ASSETS};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Suit {
     Joker(bool),
     Spades,
@@ -11,7 +14,11 @@ pub enum Suit {
     Hearts,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Spades, Suit::Diamonds, Suit::Clubs, Suit::Hearts];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Rank {
     Six,
@@ -26,6 +33,31 @@ pub enum Rank {
     Joker = u8::MAX,
 }
 
+/// What a joker was played as, a led joker names a suit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JokerCall {
+    // led, everyone plays their highest card of the suit and the joker takes it unless trumped
+    Highest(Suit),
+    // led, everyone follows the suit and its highest card takes it, the joker is the lowest
+    TakeSuit(Suit),
+    // not led, the joker takes the trick, unless a joker played after it takes it too
+    Take,
+    // not led, the joker is thrown away
+    Pass,
+}
+
+impl JokerCall {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Highest(suit) => format!("highest\n{}", format!("{suit:?}").to_lowercase()),
+            Self::TakeSuit(suit) => format!("take\n{}", format!("{suit:?}").to_lowercase()),
+            Self::Take => "take".to_string(),
+            Self::Pass => "pass".to_string(),
+        }
+    }
+}
+
 #[derive(Component, Clone, Copy, PartialEq, Debug)]
 pub struct Card {
     pub rank: Rank,
@@ -92,6 +124,10 @@ impl Card {
         string
     }
 
+    pub fn is_joker(&self) -> bool {
+        matches!(self.suit, Suit::Joker(_))
+    }
+
     pub fn value(&self, trump: Option<Suit>) -> u8 {
         if self.rank != Rank::Joker
             && let Some(t) = trump
@@ -104,22 +140,8 @@ impl Card {
     }
 
     pub fn can_place(&self, game_info: &GameInfo) -> bool {
-        // check that it's the player's turn to place
-        /*
-             if this is not the first card placed, check that the player index is last_player + 1
-             if this is the first card placed:
-                check that the player placing the card is the same one that took last cards
-                check that the player index is dealer + 1
-        */
-        if let Some(last_card) = game_info.cards_placed.front() {
-            if self.player != (last_card.player + 1) % 4 {
-                return false;
-            }
-        } else if let Some(x) = &game_info.last_took {
-            if x != &self.player {
-                return false;
-            }
-        } else if self.player != (game_info.dealer + 1) % 4 {
+        // check that everyone has called and it's the player's turn to place
+        if game_info.calling() || self.player != game_info.current_player() {
             return false;
         }
 
@@ -127,15 +149,12 @@ impl Card {
         // either:
         /*
             the suit is the same as the card being placed
-            the first card is a joker
             the player's card is a joker
+            the first card is a joker that didn't ask for a suit
         */
         // if the player doesn't have a same suit of card,
         // and they don't have a trump, they can place any card
         if let Some(first_card) = game_info.cards_placed.back() {
-            let card_suit = self.suit;
-            let first_suit = first_card.suit;
-
             // the player has already placed a card
             if game_info
                 .cards_placed
@@ -145,41 +164,228 @@ impl Card {
                 return false;
             }
 
-            /*
-                the first card or the card the player is trying to place
-                isn't a joker and they aren't the same suit
-            */
-            if !(matches!(first_suit, Suit::Joker(_)) || matches!(card_suit, Suit::Joker(_)))
-                && card_suit != first_suit
-            {
+            if self.is_joker() {
+                return true;
+            }
+
+            // a led joker asks for a suit, "highest" also wants the highest card of it
+            let (first_suit, highest) = match game_info.joker_call(first_card) {
+                Some(JokerCall::Highest(suit)) => (suit, true),
+                Some(JokerCall::TakeSuit(suit)) => (suit, false),
+                _ if first_card.is_joker() => return true,
+                _ => (first_card.suit, false),
+            };
+            let cards = &game_info.players[self.player].cards;
+
+            if cards.iter().any(|card| card.suit == first_suit) {
                 /*
                    the player has a card with the same suit as the first card
                    but is trying to place a different card
                 */
-                if game_info.players[self.player]
-                    .cards
-                    .iter()
-                    .any(|card| card.suit == first_suit)
-                {
+                if self.suit != first_suit {
                     return false;
                 }
 
-                /*
-                    the player has a trump card
-                    but is trying to place a different card
-                */
-                if let Some(trump) = game_info.trump
-                    && card_suit != trump
-                    && game_info.players[self.player]
-                        .cards
+                // or a lower one than they have when the joker asked for the highest
+                if highest
+                    && cards
                         .iter()
-                        .any(|card| card.suit == trump)
+                        .any(|card| card.suit == first_suit && card.rank as u8 > self.rank as u8)
                 {
                     return false;
                 }
+            } else if let Some(trump) = game_info.trump
+                && self.suit != trump
+                && cards.iter().any(|card| card.suit == trump)
+            {
+                /*
+                    the player has a trump card
+                    but is trying to place a different card
+                */
+                return false;
             }
         }
 
         true
     }
 }
+
+// the card that takes the trick, `cards` are in `GameInfo::cards_placed` order
+/*
+    a joker that's played as "take" wins, the last one if both are
+    a led joker that asked for the highest of a suit wins, unless someone trumps it
+    otherwise it's the highest trump, then the highest card of the suit that was led or asked for.
+    jokers nobody declared, like in old saves, take the trick too, led or not
+*/
+pub fn trick_winner(
+    cards: &VecDeque<Card>,
+    trump: Option<Suit>,
+    joker_calls: &[(Card, JokerCall)],
+) -> Card {
+    let call = |card: &Card| {
+        joker_calls
+            .iter()
+            .find(|(joker, _)| joker == card)
+            .map(|(_, call)| *call)
+    };
+    let first = *cards.back().unwrap();
+    let highest = |suit: Suit| {
+        cards
+            .iter()
+            .filter(|card| card.suit == suit)
+            .max_by_key(|card| card.rank as u8)
+            .copied()
+    };
+
+    if let Some(joker) = cards.iter().find(|card| {
+        **card != first && card.is_joker() && matches!(call(card), Some(JokerCall::Take) | None)
+    }) {
+        return *joker;
+    }
+
+    let first_suit = match (first.is_joker(), call(&first)) {
+        (true, Some(JokerCall::TakeSuit(suit))) => Some(suit),
+        (true, Some(JokerCall::Highest(suit))) => {
+            // trumps only beat it if it asked for another suit
+            return match trump {
+                Some(trump) if trump != suit => highest(trump).unwrap_or(first),
+                _ => first,
+            };
+        }
+        (true, None) => return first,
+        (true, _) => return trump.and_then(highest).unwrap_or(first),
+        (false, _) => Some(first.suit),
+    };
+
+    trump
+        .and_then(highest)
+        .or(first_suit.and_then(highest))
+        .unwrap_or(first)
+}
+
+// cards go over the wire as their asset code followed by the seat, "SA2", "JB0"
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}{}", self.as_string(), self.player))
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        let (code, player) = string.split_at_checked(2).unwrap_or((&string, ""));
+
+        if !ASSETS.contains(&code) {
+            return Err(D::Error::custom(format!("invalid card code `{code}`")));
+        }
+        // seats go from 0 to 3
+        let Ok(player @ 0..4) = player.parse() else {
+            return Err(D::Error::custom(format!("invalid seat in card `{string}`")));
+        };
+
+        Ok(Card::from_string(code.to_string(), player))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cards in play order, the first one led
+    fn trick(codes: &[&str]) -> VecDeque<Card> {
+        codes
+            .iter()
+            .enumerate()
+            .map(|(seat, code)| Card::from_string(code.to_string(), seat))
+            .rev()
+            .collect()
+    }
+
+    fn winner(codes: &[&str], trump: Option<Suit>, joker_calls: &[(usize, JokerCall)]) -> usize {
+        let cards = trick(codes);
+        let joker_calls = joker_calls
+            .iter()
+            .map(|&(seat, call)| (*cards.iter().find(|c| c.player == seat).unwrap(), call))
+            .collect::<Vec<_>>();
+        trick_winner(&cards, trump, &joker_calls).player
+    }
+
+    #[test]
+    fn off_suit_cards_dont_take_tricks() {
+        assert_eq!(winner(&["S7", "DA", "S9", "CK"], None, &[]), 2);
+        assert_eq!(
+            winner(&["S7", "DA", "S9", "H6"], Some(Suit::Hearts), &[]),
+            3
+        );
+    }
+
+    #[test]
+    fn jokers_do_what_they_were_played_as() {
+        let hearts = Some(Suit::Hearts);
+        // highest spades, only a trump beats it
+        let highest = JokerCall::Highest(Suit::Spades);
+        assert_eq!(
+            winner(&["JR", "SA", "S9", "CK"], hearts, &[(0, highest)]),
+            0
+        );
+        assert_eq!(
+            winner(&["JR", "SA", "H6", "CK"], hearts, &[(0, highest)]),
+            2
+        );
+        let highest = JokerCall::Highest(Suit::Hearts);
+        assert_eq!(
+            winner(&["JR", "HA", "S9", "CK"], hearts, &[(0, highest)]),
+            0
+        );
+        // spades take it, the joker is the lowest
+        let take_suit = JokerCall::TakeSuit(Suit::Spades);
+        assert_eq!(
+            winner(&["JR", "S7", "S9", "CK"], hearts, &[(0, take_suit)]),
+            2
+        );
+        assert_eq!(
+            winner(&["JR", "D7", "D9", "CK"], hearts, &[(0, take_suit)]),
+            0
+        );
+        // the later joker takes it from the earlier one
+        assert_eq!(
+            winner(
+                &["JR", "S7", "JB", "SA"],
+                hearts,
+                &[(0, highest), (2, JokerCall::Take)]
+            ),
+            2
+        );
+        assert_eq!(
+            winner(&["SK", "JB", "S9", "HA"], hearts, &[(1, JokerCall::Pass)]),
+            3
+        );
+        // undeclared jokers take it, even led into a trump
+        assert_eq!(winner(&["JR", "SA", "H6", "CK"], hearts, &[]), 0);
+        assert_eq!(winner(&["SK", "JB", "H6", "CK"], hearts, &[]), 1);
+    }
+
+    #[test]
+    fn led_jokers_ask_for_their_suit() {
+        let hand = ["S7", "SK", "D9", "H6"].map(|code| Card::from_string(code.to_string(), 1));
+        let mut game_info = GameInfo {
+            dealer: 3,
+            calls_made: 4,
+            trump: Some(Suit::Hearts),
+            rounds: vec![Default::default()],
+            ..Default::default()
+        };
+        for seat in 0..4 {
+            game_info.players.push_back(crate::components::Player {
+                cards: if seat == 1 { hand.to_vec() } else { vec![] },
+                ..Default::default()
+            });
+        }
+        let joker = Card::from_string("JR".to_string(), 0);
+        game_info.players[0].cards.push(joker);
+
+        game_info.place_card(joker, Some(JokerCall::Highest(Suit::Spades)));
+        let playable = hand.iter().filter(|card| card.can_place(&game_info));
+        assert_eq!(playable.collect::<Vec<_>>(), [&hand[1]]);
+    }
+}
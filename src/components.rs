@@ -1,8 +1,18 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+};
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::card::{Card, Suit};
+use crate::{
+    card::{Card, JokerCall, Suit},
+    consts::{H_PENALTIES, PLAYER_NAMES},
+    deck::{Deck, Seed},
+    record::RoundRecord,
+    settings::Settings,
+};
 
 #[derive(Resource, Default)]
 pub struct CardAssets {
@@ -10,7 +20,16 @@ pub struct CardAssets {
     pub extra: HashMap<String, Handle<Image>>,
 }
 
-#[derive(Resource, Default)]
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    Menu,
+    Playing,
+    Replay,
+    GameOver,
+}
+
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct GameInfo {
     pub _type: GameType,
     pub players: VecDeque<Player>,
@@ -21,18 +40,290 @@ pub struct GameInfo {
     pub dealer: usize,
     pub round: usize,
     pub h_penalty: i32,
+    pub seed: Seed,
+    // how many players have called this round, they call in turn starting after the dealer
+    #[serde(default)]
+    pub calls_made: usize,
+    // saves from before game records don't have this
+    #[serde(default)]
+    pub rounds: Vec<RoundRecord>,
+    // when the whole deck is dealt the player after the dealer picks the trump before calling
+    #[serde(default)]
+    pub picking_trump: bool,
+    // the card the trump was flipped from, only for display, `trump` is what counts.
+    // replays don't have it
+    #[serde(default)]
+    pub flipped: Option<Card>,
+}
+
+impl GameInfo {
+    pub fn rounds_total(&self) -> usize {
+        self._type.rounds_total()
+    }
+
+    // whoever has to call or place the next card
+    /*
+         while not everyone has called, it's the next one to call
+         if a card was already placed, it's the player after the last one
+         otherwise it's the player that took the last cards,
+         or the one after the dealer if it's the first trick
+    */
+    pub fn current_player(&self) -> usize {
+        if self.calling() {
+            (self.dealer + 1 + self.calls_made) % 4
+        } else if let Some(last_card) = self.cards_placed.front() {
+            (last_card.player + 1) % 4
+        } else if let Some(x) = self.last_took {
+            x
+        } else {
+            (self.dealer + 1) % 4
+        }
+    }
+
+    pub fn cards_per_player(&self) -> usize {
+        self.cards_in_round(self.round)
+    }
+
+    pub fn calling(&self) -> bool {
+        self.calls_made < 4
+    }
+
+    pub fn has_called(&self, seat: usize) -> bool {
+        !self.calling() || (seat + 3 - self.dealer) % 4 < self.calls_made
+    }
+
+    // the round's cards as they were dealt and what's left of the deck under them
+    pub fn deal(&self) -> (Deck, [Vec<Card>; 4]) {
+        let mut deck = Deck::shuffled(&self.seed.for_round(self.round));
+        let hands = deck.deal(self.cards_per_player());
+        (deck, hands)
+    }
+
+    // the card flipped for the trump, there's none when the trump is picked
+    pub fn trump_card(&self) -> Option<Card> {
+        self.deal().0.cards.first().copied()
+    }
+
+    // the player picking the trump chooses it after seeing their first three cards
+    pub fn first_cards(&self, seat: usize) -> Vec<Card> {
+        self.deal().1[seat].iter().take(3).copied().collect()
+    }
+
+    pub fn pick_trump(&mut self, trump: Option<Suit>) {
+        self.trump = trump;
+        self.picking_trump = false;
+        if let Some(round) = self.rounds.last_mut() {
+            round.trump = trump;
+        }
+        self.sort_hands();
+    }
+
+    pub fn sort_hands(&mut self) {
+        let trump = self.trump;
+        for player in &mut self.players {
+            player
+                .cards
+                .sort_by_key(|x| std::cmp::Reverse(x.value(trump)));
+            player.cards.sort_by_key(|x| x.suit);
+        }
+    }
+
+    // the current player calls `called` tricks
+    pub fn call(&mut self, called: i32) {
+        let seat = self.current_player();
+        self.players[seat].called = called;
+        self.calls_made += 1;
+    }
+
+    // how the jokers played this round were declared
+    pub fn joker_calls(&self) -> &[(Card, JokerCall)] {
+        self.rounds.last().map_or(&[], |round| &round.joker_calls)
+    }
+
+    pub fn joker_call(&self, card: &Card) -> Option<JokerCall> {
+        self.joker_calls()
+            .iter()
+            .find(|(joker, _)| joker == card)
+            .map(|(_, call)| *call)
+    }
+
+    // moves `card` from its player's hand to the table, jokers come with what they're played as
+    pub fn place_card(&mut self, card: Card, joker_call: Option<JokerCall>) {
+        self.players[card.player].cards.retain(|c| *c != card);
+        self.cards_placed.push_front(card);
+        if let Some(call) = joker_call
+            && let Some(round) = self.rounds.last_mut()
+        {
+            round.joker_calls.push((card, call));
+        }
+    }
+
+    // the dealer calls last and isn't allowed to make the calls add up to the number of cards
+    pub fn forbidden_call(&self, seat: usize) -> Option<i32> {
+        if seat != self.dealer {
+            return None;
+        }
+        let others = (0..self.players.len())
+            .filter(|&i| i != seat)
+            .map(|i| self.players[i].called)
+            .sum::<i32>();
+        let forbidden = self.cards_per_player() as i32 - others;
+        (forbidden >= 0).then_some(forbidden)
+    }
+
+    // the rounds of each block, see `GameType`
+    pub fn blocks(&self) -> Vec<Range<usize>> {
+        match self._type {
+            GameType::Classic => vec![0..8, 8..12, 12..20, 20..24],
+            GameType::Nines => vec![0..4, 4..8, 8..12, 12..16],
+        }
+    }
+
+    pub fn cards_in_round(&self, round: usize) -> usize {
+        if matches!(self._type, GameType::Nines) {
+            9
+        } else if (0..8).contains(&round) {
+            round + 1
+        } else if (12..20).contains(&round) {
+            8 - (round - 12) + 1
+        } else {
+            9
+        }
+    }
+}
+
+/// Who is looking at the table. A spectator has no `seat` and can't play,
+/// `show_hands` decides whether the other hands are shown face up.
+/// In `hot_seat` mode the seat follows whoever's turn it is.
+#[derive(Resource)]
+pub struct Viewer {
+    pub seat: Option<String>,
+    pub show_hands: bool,
+    pub hot_seat: bool,
+}
+
+impl Default for Viewer {
+    fn default() -> Self {
+        // a spectator until `for_game` seats it, there's no telling which seats there'll be
+        Self {
+            seat: None,
+            show_hands: true,
+            hot_seat: false,
+        }
+    }
+}
+
+impl Viewer {
+    // looks from the first human seat, several humans share the device in hot seat mode
+    // and with no humans at all everyone's just watching the bots
+    pub fn for_game(game_info: &GameInfo, settings: &Settings) -> Self {
+        let humans = game_info
+            .players
+            .iter()
+            .filter(|p| !p.bot)
+            .map(|p| p.name.clone())
+            .collect::<Vec<_>>();
+        let hot_seat = humans.len() > 1;
+
+        Self {
+            seat: humans.first().cloned(),
+            show_hands: humans.is_empty() || (settings.show_hands && !hot_seat),
+            hot_seat,
+        }
+    }
+
+    // nobody sees their hand until the trump is picked
+    pub fn can_see(&self, player: &Player, game_info: &GameInfo) -> bool {
+        !game_info.picking_trump && (self.show_hands || self.seat.as_ref() == Some(&player.name))
+    }
+
+    // whether whoever's turn it is plays from this device,
+    // bots play for themselves and in hot seat mode the device has to be passed first
+    pub fn has_turn(&self, game_info: &GameInfo) -> bool {
+        if game_info.players.is_empty() || self.seat.is_none() {
+            return false;
+        }
+        let player = &game_info.players[game_info.current_player()];
+        !player.bot && (!self.hot_seat || self.seat.as_ref() == Some(&player.name))
+    }
+
+    // bots play their own cards, spectators none at all
+    pub fn can_play(&self, card: &Card, game_info: &GameInfo) -> bool {
+        self.seat.is_some() && !game_info.players[card.player].bot && card.can_place(game_info)
+    }
+
+    /// Where `seat` sits on screen, 0 is the bottom and it goes clockwise from there.
+    pub fn table_position(&self, seat: usize, game_info: &GameInfo) -> usize {
+        let bottom = self
+            .seat
+            .as_ref()
+            .and_then(|name| game_info.players.iter().position(|p| &p.name == name))
+            .unwrap_or(0);
+        (seat + 4 - bottom) % 4
+    }
+}
+
+/// The next game as it's set up in the main menu.
+#[derive(Resource, Clone)]
+pub struct GameSetup {
+    pub game_type: GameType,
+    pub names: [String; 4],
+    pub bots: [bool; 4],
+    pub h_penalty: i32,
+    // more than one makes it a match, see `tournament`
+    pub games: usize,
+    pub duplicate: bool,
+}
+
+impl Default for GameSetup {
+    fn default() -> Self {
+        Self {
+            game_type: GameType::Classic,
+            names: PLAYER_NAMES.map(String::from),
+            bots: [true, true, true, false],
+            h_penalty: H_PENALTIES[0],
+            games: 1,
+            duplicate: false,
+        }
+    }
+}
+
+impl GameSetup {
+    // why the game can't start like this, players are told apart by their names
+    pub fn problem(&self) -> Option<&'static str> {
+        if self.names.iter().any(|name| name.trim().is_empty()) {
+            Some("Every player needs a name")
+        } else if (1..4).any(|i| self.names[..i].contains(&self.names[i])) {
+            Some("Every player needs a different name")
+        } else {
+            None
+        }
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub name: String,
     pub cards: Vec<Card>,
     pub score: i32,
     pub called: i32,
     pub taken: i32,
+    pub history: Vec<RoundScore>,
+    #[serde(default)]
+    pub bot: bool,
+}
+
+// how a finished round went for one player
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct RoundScore {
+    pub called: i32,
+    pub taken: i32,
+    pub points: i32,
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(
+    Default, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 pub enum GameType {
     // classic mode
     /*
@@ -92,8 +383,17 @@ pub enum GameType {
     Nines,
 }
 
+impl GameType {
+    pub fn rounds_total(&self) -> usize {
+        match self {
+            GameType::Classic => 24,
+            GameType::Nines => 16,
+        }
+    }
+}
+
 #[derive(Component)]
-pub struct PlayerNode;
+pub struct PlayerNode(pub usize);
 
 #[derive(Component)]
 pub struct PlacedCardsNode;
@@ -101,8 +401,56 @@ pub struct PlacedCardsNode;
 #[derive(Component)]
 pub struct PlacedCard;
 
+// what a placed joker was played as, on top of the card
+#[derive(Component)]
+pub struct JokerBadge;
+
 #[derive(Component)]
 pub struct ScoresText;
 
 #[derive(Component)]
 pub struct PlayerTag(pub String);
+
+#[derive(Component)]
+pub struct PassScreen;
+
+#[derive(Component)]
+pub struct StatsScreen;
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+#[derive(Component)]
+pub struct SaveRecordStatus;
+
+#[derive(Component)]
+pub struct MenuScreen;
+
+#[derive(Component)]
+pub struct BiddingPanel;
+
+// the joker that's being played
+#[derive(Component)]
+pub struct JokerPopup(pub Card);
+
+#[derive(Component)]
+pub struct TrumpChooser;
+
+#[derive(Component)]
+pub struct TrumpWidget;
+
+#[derive(Component)]
+pub struct TrickBanner;
+
+#[derive(Component)]
+pub struct LastTrickButton;
+
+#[derive(Component)]
+pub struct LastTrickScreen;
+
+// a finished trick stays on the table while this is set, see `hold_trick`
+#[derive(Resource, Default)]
+pub struct TrickHeld(pub bool);
+
+#[derive(Component)]
+pub struct ScoreSheetScreen;
@@ -11,3 +11,31 @@ pub const ASSETS: &[&str] = &[
 
 pub const DEFAULT_FONT_WIDTH: f32 = 20.0;
 pub const HALF_FONT_HEIGHT: f32 = DEFAULT_FONT_WIDTH * 1.2 * 0.5;
+
+pub const SAVE_FILE: &str = "autosave.json";
+pub const PROFILES_FILE: &str = "profiles.json";
+
+pub const INITIAL_RATING: f64 = 1500.0;
+// the most a player can win or lose in one game
+pub const RATING_K: f64 = 32.0;
+
+pub const MATCH_FILE: &str = "match.json";
+// the table the menu starts with
+pub const PLAYER_NAMES: [&str; 4] = ["lela", "ilia", "lizi", "giorgi"];
+pub const H_PENALTIES: [i32; 3] = [200, 500, 1000];
+pub const MAX_NAME_LENGTH: usize = 12;
+// games in a match started from the menu, one is a plain game
+pub const MATCH_LENGTHS: [usize; 3] = [1, 4, 8];
+
+pub const SETTINGS_FILE: &str = "settings.json";
+// seconds a bot waits before playing
+pub const BOT_DELAY: f32 = 0.6;
+
+// seconds a card takes to get where it's going at 1x speed
+pub const DEAL_TIME: f32 = 0.3;
+pub const DEAL_INTERVAL: f32 = 0.04;
+pub const PLAY_TIME: f32 = 0.25;
+pub const SWEEP_TIME: f32 = 0.4;
+pub const ANIMATION_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+// how long finished tricks can be kept on the table, the last one waits for a click
+pub const TRICK_PAUSES: [Option<f32>; 5] = [Some(0.0), Some(0.5), Some(1.0), Some(2.0), None];
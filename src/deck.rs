@@ -0,0 +1,170 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+use sha2::{Digest, Sha256};
+
+use crate::{card::Card, consts::ASSETS};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Seed(pub [u8; 32]);
+
+// a fresh game should never reuse a seed
+impl Default for Seed {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+impl Seed {
+    pub fn random() -> Self {
+        let mut bytes = [0; 32];
+        random_number::random_fill(&mut bytes);
+        Self(bytes)
+    }
+
+    // each round gets its own seed, so revealing one doesn't give away the next deals
+    pub fn for_round(&self, round: usize) -> Self {
+        Self(
+            Sha256::new()
+                .chain_update(self.0)
+                .chain_update((round as u64).to_le_bytes())
+                .finalize()
+                .into(),
+        )
+    }
+
+    /// What gets sent out before the deal, the hex encoded sha256 of the seed.
+    pub fn commitment(&self) -> String {
+        hex(&Sha256::digest(self.0))
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex(&self.0))
+    }
+}
+
+impl FromStr for Seed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 64 || !s.is_ascii() {
+            return Err(format!("seed `{s}` isn't 64 hex digits"));
+        }
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16)
+                .map_err(|_| format!("seed `{s}` isn't 64 hex digits"))?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for Seed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Seed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub struct Deck {
+    pub cards: Vec<Card>,
+}
+
+impl Deck {
+    /// Shuffles the 36 cards in `ASSETS` order with Fisher-Yates, where the swap for index `i`
+    /// is the first 8 bytes of `sha256(seed ++ i as u64 le)` as a little endian u64, modulo `i + 1`.
+    /// Anyone with the seed can redo this, which is what makes the deal verifiable.
+    pub fn shuffled(seed: &Seed) -> Self {
+        let mut cards = ASSETS
+            .iter()
+            .map(|code| Card::from_string(code.to_string(), usize::MAX))
+            .collect::<Vec<_>>();
+
+        for i in (1..cards.len()).rev() {
+            let hash = Sha256::new()
+                .chain_update(seed.0)
+                .chain_update((i as u64).to_le_bytes())
+                .finalize();
+            let j = u64::from_le_bytes(hash[..8].try_into().unwrap()) % (i as u64 + 1);
+            cards.swap(i, j as usize);
+        }
+
+        Self { cards }
+    }
+
+    /// Deals `count` cards to each of the 4 seats, seat 0 gets the first `count` cards
+    /// off the top, seat 1 the next `count` and so on.
+    pub fn deal(&mut self, count: usize) -> [Vec<Card>; 4] {
+        std::array::from_fn(|player| {
+            self.cards
+                .drain(..count)
+                .map(|card| Card { player, ..card })
+                .collect()
+        })
+    }
+}
+
+/// Checks a revealed seed against the commitment sent before the round,
+/// and that `hand` is exactly what `seat` should have been dealt from it.
+#[allow(dead_code)] // clients call this, the local game deals its own cards
+pub fn verify_deal(commitment: &str, seed: &Seed, seat: usize, hand: &[Card]) -> bool {
+    if seed.commitment() != commitment {
+        return false;
+    }
+
+    let dealt = &Deck::shuffled(seed).deal(hand.len())[seat];
+    hand.len() == dealt.len() && hand.iter().all(|card| dealt.contains(card))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_deal() {
+        let seed = Seed::random();
+        assert_eq!(Deck::shuffled(&seed).deal(9), Deck::shuffled(&seed).deal(9));
+        assert_ne!(
+            Deck::shuffled(&seed.for_round(0)).cards,
+            Deck::shuffled(&seed.for_round(1)).cards
+        );
+    }
+
+    #[test]
+    fn seed_hex_round_trips() {
+        let seed = Seed::random();
+        assert_eq!(seed.to_string().parse::<Seed>().unwrap(), seed);
+        assert!("abc".parse::<Seed>().is_err());
+        assert!("zz".repeat(32).parse::<Seed>().is_err());
+    }
+
+    #[test]
+    fn deal_is_verified() {
+        let seed = Seed::random();
+        let commitment = seed.commitment();
+        let mut hand = Deck::shuffled(&seed).deal(5)[2].clone();
+        hand.reverse();
+
+        assert!(verify_deal(&commitment, &seed, 2, &hand));
+        assert!(!verify_deal(&commitment, &seed, 1, &hand));
+        assert!(!verify_deal(&commitment, &Seed::random(), 2, &hand));
+
+        // a stacked deck, one card swapped for another seat's
+        hand[0] = Deck::shuffled(&seed).deal(5)[3][0];
+        hand[0].player = 2;
+        assert!(!verify_deal(&commitment, &seed, 2, &hand));
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    components::{GameInfo, GameType, ScoreSheetScreen},
+    game_over::{cell, points},
+    save::write_data,
+};
+
+#[derive(Serialize)]
+struct ScoreSheet {
+    game_type: GameType,
+    players: Vec<String>,
+    rounds: Vec<RoundRow>,
+    totals: Vec<i32>,
+}
+
+#[derive(Serialize)]
+struct RoundRow {
+    round: usize,
+    cards: usize,
+    results: Vec<PlayerRow>,
+}
+
+#[derive(Serialize)]
+struct PlayerRow {
+    player: String,
+    called: i32,
+    taken: i32,
+    points: i32,
+    total: i32,
+}
+
+impl ScoreSheet {
+    fn new(game_info: &GameInfo) -> Self {
+        let rounds = game_info
+            .players
+            .iter()
+            .map(|player| player.history.len())
+            .min()
+            .unwrap_or(0);
+
+        Self {
+            game_type: game_info._type,
+            players: game_info.players.iter().map(|p| p.name.clone()).collect(),
+            rounds: (0..rounds)
+                .map(|round| RoundRow {
+                    round: round + 1,
+                    cards: game_info.cards_in_round(round),
+                    results: game_info
+                        .players
+                        .iter()
+                        .map(|player| {
+                            let score = player.history[round];
+                            PlayerRow {
+                                player: player.name.clone(),
+                                called: score.called,
+                                taken: score.taken,
+                                points: score.points,
+                                total: player.history[..=round].iter().map(|s| s.points).sum(),
+                            }
+                        })
+                        .collect(),
+                })
+                .collect(),
+            totals: game_info.players.iter().map(|p| p.score).collect(),
+        }
+    }
+
+    // one row per player per round, the easiest shape to paste into a spreadsheet
+    fn to_csv(&self) -> String {
+        let mut csv = "round,cards,player,called,taken,points,total\n".to_string();
+        for round in &self.rounds {
+            for row in &round.results {
+                csv += &format!(
+                    "{},{},{},{},{},{},{}\n",
+                    round.round,
+                    round.cards,
+                    csv_field(&row.player),
+                    row.called,
+                    row.taken,
+                    row.points,
+                    row.total
+                );
+            }
+        }
+        csv
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// writes the score sheet next to the game record, as `scores/<file_stem>.csv` and `.json`
+pub fn export_scores(game_info: &GameInfo, file_stem: &str) {
+    let sheet = ScoreSheet::new(game_info);
+    let json = serde_json::to_string_pretty(&sheet).unwrap();
+
+    for (extension, contents) in [("csv", sheet.to_csv()), ("json", json)] {
+        match write_data(&format!("scores/{file_stem}.{extension}"), &contents) {
+            Ok(path) => info!("score sheet written to {}", path.display()),
+            Err(err) => warn!("couldn't write the score sheet: {err}"),
+        }
+    }
+}
+
+// the score sheet of the game so far, every player's call, take and points for each round
+pub fn toggle_score_sheet(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    screen: Query<Entity, With<ScoreSheetScreen>>,
+) {
+    if !screen.is_empty() {
+        for entity in screen {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let sheet = ScoreSheet::new(&game_info);
+    let mut table = commands.spawn((
+        Node {
+            display: Display::Grid,
+            grid_template_columns: RepeatedGridTrack::auto(sheet.players.len() as u16 + 2),
+            margin: UiRect::vertical(Val::Px(20.0)),
+            ..Default::default()
+        },
+        Pickable::IGNORE,
+    ));
+    table.with_children(|table| {
+        table.spawn((cell("Round"), Pickable::IGNORE));
+        table.spawn((cell("Cards"), Pickable::IGNORE));
+        for player in &sheet.players {
+            table.spawn((cell(player.clone()), Pickable::IGNORE));
+        }
+
+        for round in &sheet.rounds {
+            table.spawn((cell(round.round.to_string()), Pickable::IGNORE));
+            table.spawn((cell(round.cards.to_string()), Pickable::IGNORE));
+            for row in &round.results {
+                let text = format!("{}/{} {}", row.called, row.taken, points(row.points));
+                table.spawn((cell(text), Pickable::IGNORE));
+            }
+        }
+
+        table.spawn((cell("Total"), Pickable::IGNORE));
+        table.spawn((cell(""), Pickable::IGNORE));
+        for total in &sheet.totals {
+            table.spawn((cell(points(*total)), Pickable::IGNORE));
+        }
+    });
+    let table = table.id();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(20.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                overflow: Overflow::scroll_y(),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            GlobalZIndex(i32::MAX - 1),
+            ScoreSheetScreen,
+        ))
+        .with_child((Text::new("Score sheet (click to close)"), Pickable::IGNORE))
+        .add_child(table)
+        .observe(|trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.entity(trigger.target).despawn();
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Player, RoundScore};
+
+    #[test]
+    fn csv_has_running_totals() {
+        let mut game_info = GameInfo::default();
+        for name in ["giorgi", "lela, the second", "ilia", "lizi"] {
+            game_info.players.push_back(Player {
+                name: name.to_string(),
+                history: vec![
+                    RoundScore {
+                        called: 1,
+                        taken: 1,
+                        points: 100,
+                    },
+                    RoundScore {
+                        called: 1,
+                        taken: 0,
+                        points: -200,
+                    },
+                ],
+                score: -100,
+                ..Default::default()
+            });
+        }
+
+        let csv = ScoreSheet::new(&game_info).to_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1 + 2 * 4);
+        assert_eq!(lines[1], "1,1,giorgi,1,1,100,100");
+        assert_eq!(lines[2], "1,1,\"lela, the second\",1,1,100,100");
+        assert_eq!(lines[5], "2,2,giorgi,1,0,-200,-100");
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::{button, components::*, record::GameRecord, tournament::Match};
+
+// how the last game ended, kept for the game over screen
+#[derive(Resource)]
+pub struct GameResult {
+    pub game_info: GameInfo,
+    // what was written to `records/`
+    pub record: GameRecord,
+    // new rating and how much it changed, by seat
+    pub ratings: Vec<(f64, f64)>,
+    pub standings: Option<String>,
+}
+
+// seats from the best score to the worst, with their place, tied players share it
+fn ranking(game_info: &GameInfo) -> Vec<(usize, usize)> {
+    let mut seats = (0..game_info.players.len()).collect::<Vec<_>>();
+    seats.sort_by_key(|&seat| std::cmp::Reverse(game_info.players[seat].score));
+
+    seats
+        .iter()
+        .map(|&seat| {
+            let score = game_info.players[seat].score;
+            let better = game_info.players.iter().filter(|p| p.score > score).count();
+            (better + 1, seat)
+        })
+        .collect()
+}
+
+pub fn points(points: i32) -> String {
+    format!("{:+.2}", points as f32 / 100.0)
+}
+
+pub fn cell(text: impl Into<String>) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont::from_font_size(18.0),
+        Node {
+            padding: UiRect::axes(Val::Px(10.0), Val::Px(2.0)),
+            ..Default::default()
+        },
+    )
+}
+
+pub fn enter_game_over(
+    mut commands: Commands,
+    result: Res<GameResult>,
+    current_match: Option<Res<Match>>,
+) {
+    let game_info = &result.game_info;
+    let blocks = game_info.blocks();
+
+    let mut table = commands.spawn(Node {
+        display: Display::Grid,
+        grid_template_columns: RepeatedGridTrack::auto(blocks.len() as u16 + 6),
+        margin: UiRect::vertical(Val::Px(20.0)),
+        ..Default::default()
+    });
+    table.with_children(|table| {
+        table.spawn(cell(""));
+        table.spawn(cell("Player"));
+        table.spawn(cell("Score"));
+        table.spawn(cell("Rating"));
+        for block in &blocks {
+            table.spawn(cell(format!("Rounds {}-{}", block.start + 1, block.end)));
+        }
+        table.spawn(cell("Best round"));
+        table.spawn(cell("Worst round"));
+
+        for (place, seat) in ranking(game_info) {
+            let player = &game_info.players[seat];
+            table.spawn(cell(format!("{place}.")));
+            table.spawn(cell(player.name.clone()));
+            table.spawn(cell(points(player.score)));
+            table.spawn(cell(match result.ratings.get(seat) {
+                Some((rating, change)) => format!("{rating:.0} ({change:+.0})"),
+                None => String::new(),
+            }));
+            for block in &blocks {
+                let history = player.history.get(block.clone()).unwrap_or_default();
+                table.spawn(cell(points(history.iter().map(|r| r.points).sum())));
+            }
+
+            // the first one wins ties, the earliest best round and the earliest worst one
+            let rounds = player.history.iter().enumerate();
+            let best = rounds.clone().rev().max_by_key(|(_, r)| r.points);
+            let worst = rounds.min_by_key(|(_, r)| r.points);
+            for round in [best, worst] {
+                table.spawn(cell(match round {
+                    Some((i, r)) => format!("{} in round {}", points(r.points), i + 1),
+                    None => String::new(),
+                }));
+            }
+        }
+    });
+    let table = table.id();
+
+    let next_game = if current_match.is_some() {
+        "Next game"
+    } else {
+        "Rematch"
+    };
+    let buttons = commands
+        .spawn(Node::default())
+        .with_children(|buttons| {
+            buttons.spawn(button(next_game)).observe(
+                |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                    commands.run_system_cached(rematch);
+                },
+            );
+            buttons.spawn(button("New game")).observe(new_game);
+            buttons.spawn(button("Save record")).observe(save_record);
+            buttons.spawn(button("Main menu")).observe(
+                |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                    commands.set_state(AppState::Menu);
+                },
+            );
+        })
+        .id();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(20.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                overflow: Overflow::scroll_y(),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            GameOverScreen,
+        ))
+        .with_child((
+            Text::new("Game over (Enter for the next game)"),
+            TextFont::from_font_size(32.0),
+        ))
+        .add_child(table)
+        .with_child(Text::new(result.standings.clone().unwrap_or_default()))
+        .add_child(buttons)
+        .with_child((Text::default(), SaveRecordStatus));
+}
+
+pub fn exit_game_over(mut commands: Commands, screen: Query<Entity, With<GameOverScreen>>) {
+    for entity in screen {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<GameResult>();
+}
+
+// same players in the same seats, unless a match is on, then it's the match's next game
+pub fn rematch(mut commands: Commands, result: Res<GameResult>, current_match: Option<Res<Match>>) {
+    let mut game_info = GameInfo::default();
+    if current_match.is_none() {
+        for player in &result.game_info.players {
+            game_info.players.push_back(Player {
+                name: player.name.clone(),
+                bot: player.bot,
+                ..Default::default()
+            });
+        }
+    }
+
+    commands.insert_resource(game_info);
+    commands.set_state(AppState::Playing);
+}
+
+// starts over with the default table, a running match is given up
+fn new_game(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    Match::delete();
+    commands.remove_resource::<Match>();
+    commands.insert_resource(GameInfo::default());
+    commands.set_state(AppState::Playing);
+}
+
+// the data directory already has a copy, this one goes next to the game where it's easy to find
+fn save_record(
+    _: Trigger<Pointer<Click>>,
+    result: Res<GameResult>,
+    mut status: Single<&mut Text, With<SaveRecordStatus>>,
+) {
+    let file = format!("joker-{}.txt", result.record.file_stem());
+
+    status.0 = match fs::write(&file, result.record.to_text()) {
+        Ok(()) => format!("Record saved to {file}"),
+        Err(err) => format!("Couldn't save the record: {err}"),
+    };
+}
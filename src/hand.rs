@@ -0,0 +1,223 @@
+/*
+    Playing cards from the hand, with a mouse or a touch screen.
+    A card is played by clicking it or by dragging it onto the table,
+    it snaps back if it can't be played or is dropped anywhere else.
+    With `tap_to_confirm` on, the first click or tap only picks the card out and a second one plays it.
+*/
+
+use bevy::{ecs::query::QueryFilter, prelude::*};
+
+use crate::{
+    animation::Tween, card::Card, components::*, consts::PLAY_TIME, joker::open_joker_popup,
+    layout::Layout, settings::Settings, spawn_table, undo::UndoHistory,
+};
+
+// moving less than this is a shaky click, not a drag
+const DRAG_SLOP: f32 = 8.0;
+// how far a picked card sticks out of the hand
+const SELECT_LIFT: f32 = 20.0;
+
+/// The card picked out in tap to confirm mode, `spawn_table` drops it when the hand's rebuilt.
+#[derive(Resource, Default)]
+pub struct SelectedCard(pub Option<Entity>);
+
+// where a dragged card was picked up from
+#[derive(Component)]
+pub struct Dragged {
+    pub home: Transform,
+}
+
+// plays a card from the hand, jokers get placed once it's decided what they're played as
+pub fn play_card(
+    In(card): In<Card>,
+    mut commands: Commands,
+    mut game_info: ResMut<GameInfo>,
+    mut history: ResMut<UndoHistory>,
+    viewer: Res<Viewer>,
+) {
+    if !viewer.can_play(&card, &game_info) {
+        return;
+    }
+
+    if card.is_joker() {
+        commands.run_system_cached_with(open_joker_popup, card);
+        return;
+    }
+
+    history.push(&game_info);
+    game_info.place_card(card, None);
+    commands.run_system_cached(spawn_table);
+}
+
+#[allow(clippy::type_complexity)]
+pub fn lift_card(
+    trigger: Trigger<Pointer<Over>>,
+    mut cards: Query<(&mut Transform, &Card, Has<Tween>, Has<Dragged>), Without<PlacedCard>>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+) {
+    // touches don't hover, they'd leave the card sticking out
+    if trigger.pointer_id.is_touch() {
+        return;
+    }
+    let Ok((mut transform, card, moving, dragged)) = cards.get_mut(trigger.target) else {
+        return;
+    };
+
+    if moving || dragged || !viewer.can_play(card, &game_info) {
+        return;
+    }
+
+    transform.translation.y += 7.5;
+    transform.scale.y *= 1.2;
+}
+
+#[allow(clippy::type_complexity)]
+pub fn lower_card(
+    trigger: Trigger<Pointer<Out>>,
+    mut cards: Query<(&mut Transform, &Card, Has<Tween>, Has<Dragged>), Without<PlacedCard>>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+) {
+    if trigger.pointer_id.is_touch() {
+        return;
+    }
+    let Ok((mut transform, card, moving, dragged)) = cards.get_mut(trigger.target) else {
+        return;
+    };
+
+    if moving || dragged || !viewer.can_play(card, &game_info) {
+        return;
+    }
+
+    transform.translation.y -= 7.5;
+    transform.scale.y /= 1.2;
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn click_card(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut sprites: Query<(&mut Transform, &mut Sprite), (With<Card>, Without<PlacedCard>)>,
+    cards: Query<(&Card, Option<&Dragged>)>,
+    mut selected: ResMut<SelectedCard>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    settings: Res<Settings>,
+) {
+    let entity = trigger.target;
+    let (Ok((card, dragged)), Ok((transform, _))) = (cards.get(entity), sprites.get(entity)) else {
+        return;
+    };
+
+    // a real drag gets played or snapped back when it ends
+    if let Some(dragged) = dragged
+        && (transform.translation - dragged.home.translation)
+            .truncate()
+            .length()
+            > DRAG_SLOP
+    {
+        return;
+    }
+    if !viewer.can_play(card, &game_info) {
+        return;
+    }
+
+    if settings.tap_to_confirm && selected.0 != Some(entity) {
+        select_card(Some(entity), &mut sprites, &mut selected);
+        return;
+    }
+    commands.run_system_cached_with(play_card, *card);
+}
+
+// picks the card out of the hand, tinted and sticking out, and puts the one picked before back
+pub fn select_card<F: QueryFilter>(
+    entity: Option<Entity>,
+    sprites: &mut Query<(&mut Transform, &mut Sprite), F>,
+    selected: &mut SelectedCard,
+) {
+    if let Some(old) = selected.0.take()
+        && let Ok((mut transform, mut sprite)) = sprites.get_mut(old)
+    {
+        transform.translation.y -= SELECT_LIFT;
+        sprite.color = Color::WHITE;
+    }
+    if let Some(entity) = entity
+        && let Ok((mut transform, mut sprite)) = sprites.get_mut(entity)
+    {
+        transform.translation.y += SELECT_LIFT;
+        sprite.color = Color::srgb(0.75, 0.9, 1.0);
+        selected.0 = Some(entity);
+    }
+}
+
+pub fn start_drag(
+    trigger: Trigger<Pointer<DragStart>>,
+    mut commands: Commands,
+    cards: Query<(&Transform, &Card, Has<Tween>), Without<PlacedCard>>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+) {
+    let Ok((transform, card, moving)) = cards.get(trigger.target) else {
+        return;
+    };
+    if moving || !viewer.can_play(card, &game_info) {
+        return;
+    }
+    commands
+        .entity(trigger.target)
+        .insert(Dragged { home: *transform });
+}
+
+pub fn drag_card(
+    trigger: Trigger<Pointer<Drag>>,
+    mut cards: Query<(&mut Transform, &GlobalTransform), With<Dragged>>,
+) {
+    let Ok((mut transform, global)) = cards.get_mut(trigger.target) else {
+        return;
+    };
+    // the hand's node is turned and the pointer's y goes down the screen
+    let delta = global.rotation().inverse() * Vec3::new(trigger.delta.x, -trigger.delta.y, 0.0);
+    transform.translation += delta;
+    // over the rest of the hand
+    transform.translation.z = 1.0;
+}
+
+// a card dropped on the table gets played, anywhere else or when it can't be played it goes back
+pub fn end_drag(
+    trigger: Trigger<Pointer<DragEnd>>,
+    mut commands: Commands,
+    cards: Query<(&Transform, &Card, &Dragged)>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    settings: Res<Settings>,
+    layout: Res<Layout>,
+) {
+    let entity = trigger.target;
+    let Ok((transform, card, dragged)) = cards.get(entity) else {
+        return;
+    };
+    let home = dragged.home;
+    commands.entity(entity).try_remove::<Dragged>();
+
+    let moved = (transform.translation - home.translation).truncate();
+    // the click already dealt with it
+    if moved.length() <= DRAG_SLOP {
+        commands.entity(entity).try_insert(home);
+        return;
+    }
+
+    let on_table = moved.y > layout.card_size().y;
+    if on_table && viewer.can_play(card, &game_info) {
+        commands.run_system_cached_with(play_card, *card);
+        // the table's rebuilt and the card slides on from where it was dropped
+        if !card.is_joker() {
+            return;
+        }
+    }
+
+    match Tween::new(*transform, home, PLAY_TIME, 0.0, &settings) {
+        Some(tween) => commands.entity(entity).try_insert(tween),
+        None => commands.entity(entity).try_insert(home),
+    };
+}
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+use crate::{
+    button,
+    card::{Card, JokerCall, Suit},
+    components::*,
+    spawn_table,
+    undo::UndoHistory,
+};
+
+// a led joker names a suit, otherwise it takes the trick or passes
+pub fn joker_options(game_info: &GameInfo) -> Vec<JokerCall> {
+    if game_info.cards_placed.is_empty() {
+        Suit::ALL
+            .iter()
+            .flat_map(|&suit| [JokerCall::Highest(suit), JokerCall::TakeSuit(suit)])
+            .collect()
+    } else {
+        vec![JokerCall::Take, JokerCall::Pass]
+    }
+}
+
+// asks how a joker is played before it's placed, the number keys pick the options too
+pub fn open_joker_popup(
+    In(card): In<Card>,
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    popup: Query<Entity, With<JokerPopup>>,
+) {
+    for entity in popup {
+        commands.entity(entity).despawn();
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            JokerPopup(card),
+        ))
+        .with_children(|popup| {
+            popup.spawn(Text::new("Play the joker as"));
+            popup
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::auto(2),
+                    ..Default::default()
+                })
+                .with_children(|buttons| {
+                    for (i, call) in joker_options(&game_info).into_iter().enumerate() {
+                        let label = format!("{}: {}", i + 1, call.label().replace('\n', " "));
+                        buttons.spawn(button(&label)).observe(
+                            move |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                commands.run_system_cached_with(declare_joker, (card, call));
+                            },
+                        );
+                    }
+                });
+            popup.spawn(button("0: cancel")).observe(
+                |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                    commands.run_system_cached(close_joker_popup);
+                },
+            );
+        });
+}
+
+pub fn close_joker_popup(mut commands: Commands, popup: Query<Entity, With<JokerPopup>>) {
+    for entity in popup {
+        commands.entity(entity).despawn();
+    }
+}
+
+// places the joker as `call`
+pub fn declare_joker(
+    In((card, call)): In<(Card, JokerCall)>,
+    mut commands: Commands,
+    mut game_info: ResMut<GameInfo>,
+    mut history: ResMut<UndoHistory>,
+    popup: Query<Entity, With<JokerPopup>>,
+) {
+    for entity in popup {
+        commands.entity(entity).despawn();
+    }
+    // the game could've moved on under the popup
+    if !card.can_place(&game_info) {
+        return;
+    }
+
+    history.push(&game_info);
+    game_info.place_card(card, Some(call));
+    commands.run_system_cached(spawn_table);
+}
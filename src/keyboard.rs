@@ -0,0 +1,137 @@
+/*
+    Playing without a mouse.
+    Left/Right go through the cards that can be played and Enter plays the one picked out.
+    The number keys call, pick the trump (1-4 in the order on the buttons, 0 for no trump)
+    and say what a joker is played as. Enter also takes the device in hot seat mode.
+*/
+
+use bevy::prelude::*;
+
+use crate::{
+    bidding::make_call,
+    card::{Card, Suit},
+    components::*,
+    hand::{SelectedCard, play_card, select_card},
+    joker::{close_joker_popup, declare_joker, joker_options},
+    trump::pick_trump,
+};
+
+const DIGITS: [(KeyCode, KeyCode); 10] = [
+    (KeyCode::Digit0, KeyCode::Numpad0),
+    (KeyCode::Digit1, KeyCode::Numpad1),
+    (KeyCode::Digit2, KeyCode::Numpad2),
+    (KeyCode::Digit3, KeyCode::Numpad3),
+    (KeyCode::Digit4, KeyCode::Numpad4),
+    (KeyCode::Digit5, KeyCode::Numpad5),
+    (KeyCode::Digit6, KeyCode::Numpad6),
+    (KeyCode::Digit7, KeyCode::Numpad7),
+    (KeyCode::Digit8, KeyCode::Numpad8),
+    (KeyCode::Digit9, KeyCode::Numpad9),
+];
+
+fn digit(keys: &ButtonInput<KeyCode>) -> Option<usize> {
+    DIGITS
+        .iter()
+        .position(|(digit, numpad)| keys.any_just_pressed([*digit, *numpad]))
+}
+
+fn enter(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter])
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn keyboard_play(
+    mut commands: Commands,
+    mut sprites: Query<(&mut Transform, &mut Sprite), (With<Card>, Without<PlacedCard>)>,
+    mut selected: ResMut<SelectedCard>,
+    hand: Query<(Entity, &Card), Without<PlacedCard>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    pass_screen: Query<Entity, With<PassScreen>>,
+    popups: Query<(), Or<(With<JokerPopup>, With<TrumpChooser>)>>,
+) {
+    if !pass_screen.is_empty() {
+        if enter(&keys) {
+            for entity in pass_screen {
+                commands.entity(entity).despawn();
+            }
+        }
+        return;
+    }
+    if !popups.is_empty() {
+        return;
+    }
+
+    // in the order they're in the hand
+    let mut legal = hand
+        .iter()
+        .filter(|(_, card)| viewer.can_play(card, &game_info))
+        .collect::<Vec<_>>();
+    if legal.is_empty() {
+        return;
+    }
+    let cards = &game_info.players[legal[0].1.player].cards;
+    legal.sort_by_key(|(_, card)| cards.iter().position(|c| c == *card));
+
+    let current = selected
+        .0
+        .and_then(|entity| legal.iter().position(|(legal, _)| *legal == entity));
+    let next = if keys.just_pressed(KeyCode::ArrowRight) {
+        Some(current.map_or(0, |i| (i + 1) % legal.len()))
+    } else if keys.just_pressed(KeyCode::ArrowLeft) {
+        Some(current.map_or(legal.len() - 1, |i| (i + legal.len() - 1) % legal.len()))
+    } else {
+        None
+    };
+
+    if let Some(next) = next {
+        select_card(Some(legal[next].0), &mut sprites, &mut selected);
+    } else if enter(&keys)
+        && let Some(current) = current
+    {
+        commands.run_system_cached_with(play_card, *legal[current].1);
+    }
+}
+
+// the number keys for whatever's being asked, a call, the trump or what a joker is played as
+pub fn keyboard_choose(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    pass_screen: Query<(), With<PassScreen>>,
+    joker_popup: Query<&JokerPopup>,
+) {
+    let Some(digit) = digit(&keys) else {
+        return;
+    };
+    if !pass_screen.is_empty() {
+        return;
+    }
+
+    if let Ok(JokerPopup(card)) = joker_popup.single() {
+        match digit {
+            0 => commands.run_system_cached(close_joker_popup),
+            _ => {
+                if let Some(call) = joker_options(&game_info).get(digit - 1) {
+                    commands.run_system_cached_with(declare_joker, (*card, *call));
+                }
+            }
+        }
+        return;
+    }
+
+    if !viewer.has_turn(&game_info) {
+        return;
+    }
+    if game_info.picking_trump {
+        match digit {
+            0 => commands.run_system_cached_with(pick_trump, None),
+            1..=4 => commands.run_system_cached_with(pick_trump, Some(Suit::ALL[digit - 1])),
+            _ => {}
+        }
+    } else if game_info.calling() {
+        commands.run_system_cached_with(make_call, digit as i32);
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+
+use crate::{
+    button,
+    components::*,
+    consts::{CSH, CSW},
+};
+
+pub fn spawn_last_trick_button(mut commands: Commands) {
+    commands
+        .spawn((button("Last trick"), LastTrickButton))
+        // in the bottom left corner, out of the way of the hand
+        .insert(Node {
+            bottom: Val::Px(0.0),
+            left: Val::Px(0.0),
+            padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+            margin: UiRect::all(Val::Px(10.0)),
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        })
+        .observe(|_: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.run_system_cached(toggle_last_trick);
+        });
+}
+
+// the trick that was just taken, in the order it was played and who took it
+pub fn toggle_last_trick(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    assets: Res<CardAssets>,
+    screen: Query<Entity, With<LastTrickScreen>>,
+) {
+    if !screen.is_empty() {
+        for entity in screen {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let title = match game_info.last_took {
+        Some(winner) if !game_info.last_cards_placed.is_empty() => format!(
+            "Last trick, taken by {} (click to close)",
+            game_info.players[winner].name
+        ),
+        _ => "No trick has been taken this round yet (click to close)".to_string(),
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            GlobalZIndex(i32::MAX - 1),
+            LastTrickScreen,
+        ))
+        .with_children(|screen| {
+            screen.spawn((Text::new(title), Pickable::IGNORE));
+            screen
+                .spawn((Node::default(), Pickable::IGNORE))
+                .with_children(|cards| {
+                    for card in game_info.last_cards_placed.iter().rev() {
+                        let border = if Some(card.player) == game_info.last_took {
+                            Color::srgb(1.0, 0.85, 0.3)
+                        } else {
+                            Color::NONE
+                        };
+                        cards.spawn((
+                            Node {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                margin: UiRect::all(Val::Px(8.0)),
+                                ..Default::default()
+                            },
+                            Pickable::IGNORE,
+                            children![
+                                (
+                                    ImageNode::new(assets.primary[&card.as_string()].clone()),
+                                    Node {
+                                        width: Val::Px(CSW * 1.5),
+                                        height: Val::Px(CSH * 1.5),
+                                        border: UiRect::all(Val::Px(3.0)),
+                                        ..Default::default()
+                                    },
+                                    BorderColor(border),
+                                    Pickable::IGNORE,
+                                ),
+                                (
+                                    Text::new(&game_info.players[card.player].name),
+                                    Pickable::IGNORE,
+                                ),
+                            ],
+                        ));
+                    }
+                });
+        })
+        .observe(|trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.entity(trigger.target).despawn();
+        });
+}
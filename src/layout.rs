@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use crate::consts::{CARD_HEIGHT, CARD_SCALE, CARD_WIDTH, HALF_FONT_HEIGHT};
+
+// space between a hand and the edge of the window
+const MARGIN: f32 = 25.0;
+// the table is this many card heights across, a hand on each side and the trick between them
+const TABLE_CARDS: f32 = 5.5;
+// at the most overlap this much of every card's width still shows
+const MIN_SPACING: f32 = 0.3;
+const MIN_CARD_SCALE: f32 = 1.0 / 20.0;
+
+/// Card size and spacing for the window and hand size, everything on the table is placed with it.
+/// Hands fit along the shorter side of the window, so portrait screens work the same way.
+/// `spawn_table` makes a new one every time it rebuilds the table, and so does a window resize.
+#[derive(Resource, Clone, Copy)]
+pub struct Layout {
+    pub window: Vec2,
+    pub scale: f32,
+    // from one card in a hand to the next
+    pub spacing: f32,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new(Vec2::new(1280.0, 720.0), 9)
+    }
+}
+
+impl Layout {
+    pub fn new(window: Vec2, hand_size: usize) -> Self {
+        let short_side = window.min_element();
+        let mut scale = (short_side / (TABLE_CARDS * CARD_HEIGHT)).min(CARD_SCALE);
+
+        // big hands in small windows overlap, then they get smaller once that's not enough
+        loop {
+            let width = CARD_WIDTH * scale;
+            let room = short_side - 2.0 * (CARD_HEIGHT * scale + MARGIN) - width;
+            let spacing = match hand_size {
+                0 | 1 => width,
+                n => (room / (n - 1) as f32).min(width),
+            };
+            if spacing >= width * MIN_SPACING || scale <= MIN_CARD_SCALE {
+                return Self {
+                    window,
+                    scale,
+                    spacing: spacing.max(width * MIN_SPACING),
+                };
+            }
+            scale = (scale * 0.9).max(MIN_CARD_SCALE);
+        }
+    }
+
+    pub fn card_size(&self) -> Vec2 {
+        Vec2::new(CARD_WIDTH, CARD_HEIGHT) * self.scale
+    }
+
+    // how far across from the middle of its hand the `i`th card of `len` goes
+    pub fn hand_x(&self, i: usize, len: usize) -> f32 {
+        (i as f32 - (len as f32 - 1.0) / 2.0) * self.spacing
+    }
+
+    // how far down a hand goes in its player node, the sideways ones go by the window's width
+    pub fn hand_y(&self, position: usize) -> f32 {
+        let extent = if position.is_multiple_of(2) {
+            self.window.y
+        } else {
+            self.window.x
+        };
+        (-extent + self.card_size().y + MARGIN) / 2.0
+    }
+
+    // the middle of a hand, on screen
+    pub fn hand_center(&self, position: usize) -> Vec3 {
+        Quat::from_rotation_z(position as f32 * -90f32.to_radians())
+            * Vec3::new(0.0, self.hand_y(position), 0.0)
+    }
+
+    pub fn placed_card_transform(&self, position: usize) -> Transform {
+        Transform::from_translation(
+            match position {
+                0 => Vec3::NEG_Y,
+                1 => Vec3::NEG_X,
+                2 => Vec3::Y,
+                3 => Vec3::X,
+                _ => unreachable!(),
+            } * self.card_size().y,
+        )
+        .with_rotation(Quat::from_rotation_z(position as f32 * -90f32.to_radians()))
+        .with_scale(Vec3::ONE * self.scale)
+    }
+
+    // just past the inner edge of the hand, the sideways ones are as wide as their longest line
+    pub fn nametag_y(&self, position: usize, text: &str) -> f32 {
+        let height = self.card_size().y;
+        let size = if position.is_multiple_of(2) {
+            text.lines().count() as f32
+        } else {
+            text.lines().map(|line| line.len()).max().unwrap_or(0) as f32
+        };
+        self.hand_y(position) + (size * HALF_FONT_HEIGHT - MARGIN) / 2.0 + height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hands_fit_the_window() {
+        for window in [
+            Vec2::new(1280.0, 720.0),
+            Vec2::new(800.0, 600.0),
+            Vec2::new(390.0, 844.0),
+            Vec2::new(320.0, 480.0),
+        ] {
+            for hand_size in 1..=9 {
+                let layout = Layout::new(window, hand_size);
+                let card = layout.card_size();
+                let hand = layout.spacing * (hand_size - 1) as f32 + card.x;
+                assert!(
+                    hand <= window.min_element() - 2.0 * card.y,
+                    "{window} {hand_size}"
+                );
+                assert!(layout.scale <= CARD_SCALE);
+            }
+        }
+        // there's room for the cards side by side in a normal window
+        let layout = Layout::new(Vec2::new(1280.0, 720.0), 8);
+        assert_eq!(layout.spacing, layout.card_size().x);
+    }
+}
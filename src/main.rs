@@ -1,71 +1,294 @@
+use std::path::Path;
+
 use bevy::{
     input::common_conditions::input_just_pressed,
     prelude::*,
     window::{PrimaryWindow, WindowResized},
 };
-use random_number::random;
 // use bevy_inspector_egui::{bevy_egui::EguiPlugin, quick::WorldInspectorPlugin};
 
-use crate::{card::*, components::*, consts::*};
+use crate::{
+    animation::*, bidding::*, bot::*, card::*, components::*, consts::*, export::*, game_over::*,
+    hand::*, keyboard::*, last_trick::*, layout::*, menu::*, profiles::*, rating::*, record::*,
+    replay::*, save::*, settings::*, skins::*, tournament::*, trump::*, undo::*,
+};
 
+mod animation;
+mod bidding;
+mod bot;
 mod card;
 mod components;
 mod consts;
+mod deck;
+mod export;
+mod game_over;
+mod hand;
+mod joker;
+mod keyboard;
+mod last_trick;
+mod layout;
+mod menu;
+mod profiles;
+mod protocol;
+mod rating;
+mod record;
+mod replay;
+mod save;
+mod settings;
+mod skins;
+mod tournament;
+mod trump;
+mod undo;
 
 fn main() {
-    App::new()
-        .init_resource::<CardAssets>()
+    // `cargo run -- --recompute-ratings <records dir>` rebuilds the ratings and exits
+    if let Some(dir) = std::env::args()
+        .skip_while(|arg| arg != "--recompute-ratings")
+        .nth(1)
+    {
+        if let Err(err) = recompute_ratings(Path::new(&dir)) {
+            eprintln!("couldn't recompute the ratings, {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut app = App::new();
+    app.init_resource::<CardAssets>()
         .init_resource::<GameInfo>()
+        .init_resource::<Viewer>()
+        .init_resource::<UndoHistory>()
+        .init_resource::<GameSetup>()
+        .init_resource::<EditingName>()
+        .init_resource::<TrickHeld>()
+        .init_resource::<Layout>()
+        .init_resource::<SelectedCard>()
+        .insert_resource(Profiles::load())
+        .insert_resource(Skins::scan(asset_root().as_deref()))
+        .insert_resource(Settings::load())
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Joker Game".to_string(),
+                // the web build follows the browser window, phones included
+                fit_canvas_to_parent: true,
                 ..Default::default()
             }),
             ..Default::default()
         }))
         // .add_plugins((EguiPlugin::default(), WorldInspectorPlugin::default()))
-        .add_systems(Startup, (load_assets, start_game).chain())
+        .init_state::<AppState>()
+        .add_systems(Startup, (load_assets, apply_skin).chain())
+        .add_systems(OnEnter(AppState::Menu), spawn_menu)
+        .add_systems(OnExit(AppState::Menu), exit_menu)
+        .add_systems(
+            Update,
+            (
+                start_new_game.run_if(
+                    input_just_pressed(KeyCode::Enter)
+                        .and(|editing: Res<EditingName>| editing.0.is_none()),
+                ),
+                edit_name,
+                spawn_menu.run_if(
+                    resource_changed::<GameSetup>
+                        .or(resource_changed::<Settings>)
+                        .or(resource_changed::<EditingName>),
+                ),
+            )
+                .chain()
+                .run_if(in_state(AppState::Menu)),
+        )
+        .add_systems(
+            OnEnter(AppState::Playing),
+            (continue_game, spawn_last_trick_button),
+        )
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            leave_game.run_if(in_state(AppState::Playing).and(input_just_pressed(KeyCode::Escape))),
+        )
+        .add_systems(
+            Update,
+            run_tweens.run_if(in_state(AppState::Playing).or(in_state(AppState::Replay))),
+        )
+        .add_systems(
+            Update,
+            (bot_play, hold_trick, award_scores)
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(
+            Update,
+            follow_turn
+                .after(award_scores)
+                .run_if(in_state(AppState::Playing).and(resource_changed::<GameInfo>)),
+        )
+        .add_systems(
+            Update,
+            (update_bidding_panel, update_trump_chooser)
+                .after(follow_turn)
+                .run_if(
+                    in_state(AppState::Playing)
+                        .and(resource_changed::<GameInfo>.or(resource_changed::<Viewer>)),
+                ),
+        )
+        .add_systems(
+            Update,
+            update_trump_widget.run_if(
+                in_state(AppState::Playing)
+                    .or(in_state(AppState::Replay))
+                    .and(resource_changed::<GameInfo>.or(resource_changed::<CardAssets>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            toggle_hot_seat
+                .run_if(in_state(AppState::Playing).and(input_just_pressed(KeyCode::KeyH))),
+        )
         .add_systems(
             Update,
-            (cleanup, start_game)
+            (
+                undo.run_if(ctrl_pressed.and(input_just_pressed(KeyCode::KeyZ))),
+                redo.run_if(ctrl_pressed.and(input_just_pressed(KeyCode::KeyY))),
+            )
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_stats_screen.run_if(input_just_pressed(KeyCode::KeyP)),
+                toggle_score_sheet.run_if(input_just_pressed(KeyCode::KeyS)),
+                toggle_last_trick.run_if(input_just_pressed(KeyCode::KeyL)),
+                keyboard_play,
+                keyboard_choose,
+            )
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(OnEnter(AppState::GameOver), enter_game_over)
+        .add_systems(
+            Update,
+            rematch.run_if(in_state(AppState::GameOver).and(input_just_pressed(KeyCode::Enter))),
+        )
+        .add_systems(OnExit(AppState::GameOver), exit_game_over)
+        .add_systems(OnEnter(AppState::Replay), enter_replay)
+        .add_systems(OnExit(AppState::Replay), exit_replay)
+        .add_systems(
+            Update,
+            (
+                replay_controls,
+                show_replay_step.run_if(resource_changed::<Replay>),
+            )
                 .chain()
-                .run_if(input_just_pressed(KeyCode::Space)),
+                .run_if(in_state(AppState::Replay)),
         )
-        .add_systems(Update, award_scores)
         .add_systems(
             Update,
-            (card_highlight, update_nametags).run_if(resource_changed::<GameInfo>),
+            update_table_view
+                .after(follow_turn)
+                .run_if(resource_changed::<Viewer>),
         )
-        .add_systems(Update, resize_event)
-        .run();
+        .add_systems(
+            Update,
+            (card_highlight, update_nametags)
+                .run_if(resource_changed::<GameInfo>.or(resource_changed::<Viewer>)),
+        )
+        .add_systems(Update, apply_skin.run_if(resource_changed::<Settings>))
+        .add_systems(Update, resize_event);
+
+    // `cargo run -- --match <games> [--duplicate]` starts a match, an unfinished one carries on
+    if let Some(games) = std::env::args().skip_while(|arg| arg != "--match").nth(1) {
+        let Ok(games @ 1..) = games.parse() else {
+            eprintln!("--match needs the number of games, got `{games}`");
+            std::process::exit(1);
+        };
+        let duplicate = std::env::args().any(|arg| arg == "--duplicate");
+        let new_match = Match::new(&PLAYER_NAMES, &GameSetup::default().bots, games, duplicate);
+        new_match.save();
+        delete_save();
+        app.insert_resource(new_match)
+            .insert_state(AppState::Playing);
+    } else if let Some(current_match) = Match::load() {
+        app.insert_resource(current_match);
+    }
+
+    // `cargo run -- --replay <record>` opens a game record instead of playing
+    if let Some(path) = std::env::args().skip_while(|arg| arg != "--replay").nth(1) {
+        match read_record(Path::new(&path)) {
+            Ok(record) => {
+                app.insert_resource(Replay::new(record))
+                    .insert_state(AppState::Replay);
+            }
+            Err(err) => {
+                eprintln!("couldn't open the game record {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    app.run();
 }
 
-fn load_assets(
-    mut commands: Commands,
-    mut assets: ResMut<CardAssets>,
-    asset_server: Res<AssetServer>,
-) {
+fn button(label: &str) -> impl Bundle {
+    (
+        Button,
+        Node {
+            padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+            margin: UiRect::all(Val::Px(10.0)),
+            ..Default::default()
+        },
+        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        children![(Text::new(label), Pickable::IGNORE)],
+    )
+}
+
+fn ctrl_pressed(keys: Res<ButtonInput<KeyCode>>) -> bool {
+    keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+fn load_assets(mut commands: Commands) {
     commands.spawn(Camera2d);
-    for name in ASSETS.iter() {
-        assets.primary.insert(
-            name.to_string(),
-            asset_server.load(format!("cards/{}.png", name)),
-        );
-    }
-    assets
-        .extra
-        .insert("back".to_string(), asset_server.load("back/R.png"));
 }
 
-fn start_game(mut commands: Commands, mut game_info: ResMut<GameInfo>) {
+fn start_game(
+    mut commands: Commands,
+    mut game_info: ResMut<GameInfo>,
+    mut viewer: ResMut<Viewer>,
+    setup: Res<GameSetup>,
+    settings: Res<Settings>,
+    current_match: Option<Res<Match>>,
+) {
+    game_info._type = setup.game_type;
     game_info.dealer = 3;
-    game_info.h_penalty = 200;
+    game_info.h_penalty = setup.h_penalty;
 
-    for name in ["lela", "ilia", "lizi", "giorgi"] {
-        game_info.players.push_back(Player {
-            name: name.to_string(),
-            ..Default::default()
-        });
+    // a rematch comes with the players already seated,
+    // otherwise a match decides the seats itself, they rotate between games
+    if game_info.players.is_empty() {
+        let seats = match &current_match {
+            Some(current_match) => current_match.seating(),
+            None => setup.names.iter().cloned().zip(setup.bots).collect(),
+        };
+        for (name, bot) in seats {
+            game_info.players.push_back(Player {
+                name,
+                bot,
+                ..Default::default()
+            });
+        }
+    }
+    *viewer = Viewer::for_game(&game_info, &settings);
+
+    if let Some(current_match) = &current_match {
+        if current_match.duplicate {
+            game_info.seed = current_match.seed;
+        }
+    }
+    // spectators have no seat to put at the bottom, the table stays as it is.
+    // seats don't move after this, the dealer and the game record go by them
+    else if let Some(current_player) = &viewer.seat {
+        while &game_info.players[0].name != current_player {
+            let player = game_info.players.pop_front().unwrap();
+            game_info.players.push_back(player)
+        }
     }
 
     commands.spawn((Text::new("Scores:"), ScoresText));
@@ -74,226 +297,424 @@ fn start_game(mut commands: Commands, mut game_info: ResMut<GameInfo>) {
     commands.run_system_cached(start_round);
 }
 
+// picks up the autosave if there is one
+fn continue_game(mut commands: Commands, settings: Res<Settings>) {
+    let Some(game_info) = load_game() else {
+        commands.run_system_cached(start_game);
+        return;
+    };
+
+    commands.insert_resource(Viewer::for_game(&game_info, &settings));
+    commands.insert_resource(game_info);
+    commands.spawn((Text::new("Scores:"), ScoresText));
+    commands.spawn((Visibility::Visible, Transform::default(), PlacedCardsNode));
+    commands.run_system_cached(spawn_table);
+}
+
 fn start_round(
     mut commands: Commands,
     mut game_info: ResMut<GameInfo>,
+    mut history: ResMut<UndoHistory>,
+) {
+    // undoing only goes back to the start of the round
+    history.clear();
+
+    debug!(
+        "round {} deck commitment: {}",
+        game_info.round,
+        game_info.seed.for_round(game_info.round).commitment()
+    );
+    let (_, hands) = game_info.deal();
+
+    // the card under the dealt ones, when the whole deck is out the trump gets picked
+    let trump_card = game_info.trump_card();
+    game_info.flipped = trump_card;
+    game_info.picking_trump = trump_card.is_none();
+    game_info.trump = trump_card.and_then(|card| match card.suit {
+        Suit::Joker(_) => None,
+        suit => Some(suit),
+    });
+    let trump = game_info.trump;
+
+    for (player, cards) in game_info.players.iter_mut().zip(hands) {
+        player.cards = cards;
+    }
+    game_info.sort_hands();
+
+    game_info.calls_made = 0;
+
+    let round = RoundRecord {
+        dealer: game_info.dealer,
+        trump,
+        hands: game_info.players.iter().map(|p| p.cards.clone()).collect(),
+        ..Default::default()
+    };
+    game_info.rounds.push(round);
+
+    save_game(&game_info);
+    commands.run_system_cached(spawn_table);
+}
+
+// (re)builds the hands and the current trick from `GameInfo`,
+// cards that moved slide over from where they were and new ones get dealt from the dealer
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn spawn_table(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
     assets: Res<CardAssets>,
+    viewer: Res<Viewer>,
+    settings: Res<Settings>,
     window: Single<&Window, With<PrimaryWindow>>,
+    old_table: Query<Entity, Or<(With<PlayerNode>, With<PlacedCard>)>>,
+    old_cards: Query<(&GlobalTransform, &Card)>,
+    placed_cards_node: Single<Entity, With<PlacedCardsNode>>,
 ) {
-    let mut deck = assets.primary.keys().cloned().collect::<Vec<_>>();
+    let old_cards = old_cards
+        .iter()
+        .map(|(transform, card)| (*card, transform.compute_transform()))
+        .collect::<Vec<_>>();
+    let was = |card: &Card| {
+        old_cards
+            .iter()
+            .find(|(old, _)| old == card)
+            .map(|(_, transform)| *transform)
+    };
 
-    let current_player = "giorgi";
-    while game_info.players[0].name != current_player {
-        let player = game_info.players.pop_front().unwrap();
-        game_info.players.push_back(player)
+    for entity in old_table {
+        commands.entity(entity).despawn();
     }
 
-    game_info.trump = Some(Card::from_string(deck[random!(..deck.len())].clone(), usize::MAX).suit);
+    let layout = Layout::new(window.size(), game_info.cards_per_player());
+    commands.insert_resource(layout);
+    commands.insert_resource(SelectedCard::default());
 
-    let _type = game_info._type;
-    let round = game_info.round;
-    let trump = game_info.trump;
+    for card in game_info.cards_placed.iter().rev() {
+        let mut placed_card = commands.spawn((
+            Sprite::from_image(assets.primary[&card.as_string()].clone()),
+            *card,
+            PlacedCard,
+            ChildOf(*placed_cards_node),
+        ));
+        // the placed cards node sits in the middle of the screen, unturned
+        animate(
+            &mut placed_card,
+            was(card),
+            layout.placed_card_transform(viewer.table_position(card.player, &game_info)),
+            PLAY_TIME,
+            0.0,
+            &settings,
+        );
+        let placed_card = placed_card.id();
 
-    for (i, player) in game_info.players.iter_mut().enumerate() {
-        for _ in if matches!(_type, GameType::Nines) {
-            0..9
-        } else if (0..8).contains(&round) {
-            0..round + 1
-        } else if (12..20).contains(&round) {
-            0..(8 - (round - 12)) + 1
-        } else {
-            0..9
-        } {
-            let card = deck.remove(random!(..deck.len()));
-            player.cards.push(Card::from_string(card, i));
+        // what the joker was played as, for everyone to see
+        if let Some(call) = game_info.joker_call(card) {
+            commands.spawn((
+                Sprite::from_color(Color::srgba(0.0, 0.0, 0.0, 0.8), Vec2::new(70.0, 32.0)),
+                Transform::from_xyz(0.0, 0.0, 1.0).with_scale(Vec3::ONE / layout.scale),
+                JokerBadge,
+                ChildOf(placed_card),
+                children![(
+                    Text2d::new(call.label()),
+                    TextFont::from_font_size(12.0),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    Transform::from_xyz(0.0, 0.0, 1.0),
+                )],
+            ));
         }
-        player
-            .cards
-            .sort_by_key(|x| std::cmp::Reverse(x.value(trump)));
-        player.cards.sort_by_key(|x| x.suit);
     }
 
+    let dealer_position = viewer.table_position(game_info.dealer, &game_info);
+    let dealer_pile = Transform::from_translation(layout.hand_center(dealer_position))
+        .with_rotation(Quat::from_rotation_z(
+            dealer_position as f32 * -90f32.to_radians(),
+        ))
+        .with_scale(Vec3::ONE * layout.scale);
+
     for (i, player) in game_info.players.iter().enumerate() {
+        let position = viewer.table_position(i, &game_info);
+        let node_transform =
+            Transform::from_rotation(Quat::from_rotation_z(position as f32 * -90f32.to_radians()));
         let player_node = commands
-            .spawn((
-                Visibility::Visible,
-                Transform::from_rotation(Quat::from_rotation_z(i as f32 * -90f32.to_radians())),
-                PlayerNode,
-            ))
+            .spawn((Visibility::Visible, node_transform, PlayerNode(i)))
             .id();
+        // cards go out one at a time starting left of the dealer
+        let deal_turn = (i + 3 - game_info.dealer) % 4;
         commands.spawn((
             Text2d::new(&player.name),
             TextLayout::new_with_justify(JustifyText::Center),
             PlayerTag(player.name.clone()),
-            Transform::from_rotation(Quat::from_rotation_z(i as f32 * 90f32.to_radians())),
+            Transform::from_rotation(Quat::from_rotation_z(position as f32 * 90f32.to_radians())),
             ChildOf(player_node),
         ));
 
         for (j, card) in player.cards.iter().enumerate() {
-            commands
-                .spawn((
-                    Pickable::default(),
-                    Sprite::from_image(if player.name == current_player {
-                        assets.primary[&card.as_string()].clone()
-                    } else {
-                        // assets.extra["back"].clone()
-                        assets.primary[&card.as_string()].clone()
-                    }),
-                    Transform::from_xyz(
-                        (j as f32 - (player.cards.len() as f32 - 1.0) / 2.0) * CSW,
-                        if i % 2 == 0 {
-                            (-window.height() + CSH + 25.0) / 2.0
-                        } else {
-                            (-window.width() + CSW + 25.0) / 2.0
-                        },
-                        0.0,
-                    )
-                    .with_scale(Vec3::ONE * CARD_SCALE),
-                    *card,
-                    ChildOf(player_node),
-                ))
-                .observe(
-                    |trigger: Trigger<Pointer<Over>>,
-                     mut cards: Query<(&mut Transform, &Card), Without<PlacedCard>>,
-                     game_info: Res<GameInfo>| {
-                        let (mut transform, card) = cards.get_mut(trigger.target).unwrap();
-
-                        if !card.can_place(&game_info) {
-                            return;
-                        }
-
-                        // if card.player == 0 {
-                        transform.translation.y += 7.5;
-                        transform.scale.y *= 1.2;
-                        // }
-                    },
-                )
-                .observe(
-                    |trigger: Trigger<Pointer<Out>>,
-                     mut cards: Query<(&mut Transform, &Card), Without<PlacedCard>>,
-                     game_info: Res<GameInfo>| {
-                        let (mut transform, card) = cards.get_mut(trigger.target).unwrap();
-
-                        if !card.can_place(&game_info) {
-                            return;
-                        }
-
-                        // if card.player == 0 {
-                        transform.translation.y -= 7.5;
-                        transform.scale.y /= 1.2;
-                        // }
-                    },
-                )
-                .observe(
-                    |trigger: Trigger<Pointer<Released>>,
-                     mut cards: Query<(&mut Transform, &Card), Without<PlacedCard>>,
-                     mut commands: Commands,
-                     mut game_info: ResMut<GameInfo>,
-                     assets: Res<CardAssets>,
-                     placed_cards_node: Single<Entity, With<PlacedCardsNode>>| {
-                        let (_, card) = cards.get(trigger.target).unwrap();
-
-                        if !card.can_place(&game_info) {
-                            return;
-                        }
-
-                        {
-                            let GameInfo {
-                                players,
-                                cards_placed,
-                                ..
-                            } = &mut *game_info;
-
-                            let pcards = &mut players[card.player].cards;
-                            cards_placed.push_front(
-                                pcards.remove(pcards.iter().position(|x| x == card).unwrap()),
-                            );
-                            commands.entity(trigger.target).despawn();
-                            let fc = cards_placed.back().unwrap();
-                            commands.spawn((
-                                Sprite::from_image(assets.primary[&card.as_string()].clone()),
-                                Transform::from_translation(
-                                    match (cards_placed.len() - 1 + fc.player) % 4 {
-                                        0 => Vec3::NEG_Y,
-                                        1 => Vec3::NEG_X,
-                                        2 => Vec3::Y,
-                                        3 => Vec3::X,
-                                        _ => unreachable!(),
-                                    } * CSH,
-                                )
-                                .with_rotation(Quat::from_rotation_z(
-                                    (cards_placed.len() - 1 + fc.player) as f32
-                                        * -90f32.to_radians(),
-                                ))
-                                .with_scale(Vec3::ONE * CARD_SCALE),
-                                *card,
-                                PlacedCard,
-                                ChildOf(*placed_cards_node),
-                            ));
-                        }
-
-                        let pcards = &game_info.players[card.player].cards;
-                        for (mut transform, card) in cards.iter_mut() {
-                            // not my finest code
-                            if let Some(pos) = pcards.iter().position(|c| c == card) {
-                                transform.translation.x =
-                                    (pos as f32 - (pcards.len() as f32 - 1.0) / 2.0) * CSW
-                            }
-                        }
-                    },
-                );
+            let mut hand_card = commands.spawn((
+                Pickable::default(),
+                Sprite::from_image(if viewer.can_see(player, &game_info) {
+                    assets.primary[&card.as_string()].clone()
+                } else {
+                    assets.extra["back"].clone()
+                }),
+                *card,
+                ChildOf(player_node),
+            ));
+            // later cards go on top of the ones they overlap
+            let to = Transform::from_xyz(
+                layout.hand_x(j, player.cards.len()),
+                layout.hand_y(position),
+                j as f32 * 0.01,
+            )
+            .with_scale(Vec3::ONE * layout.scale);
+            match was(card) {
+                Some(from) => animate(
+                    &mut hand_card,
+                    Some(relative_to(node_transform, from)),
+                    to,
+                    PLAY_TIME,
+                    0.0,
+                    &settings,
+                ),
+                None => animate(
+                    &mut hand_card,
+                    Some(relative_to(node_transform, dealer_pile)),
+                    to,
+                    DEAL_TIME,
+                    (j * 4 + deal_turn) as f32 * DEAL_INTERVAL,
+                    &settings,
+                ),
+            }
+            hand_card
+                .observe(lift_card)
+                .observe(lower_card)
+                .observe(click_card)
+                .observe(start_drag)
+                .observe(drag_card)
+                .observe(end_drag);
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn cleanup(
     mut commands: Commands,
     player_nodes: Query<Entity, With<PlayerNode>>,
     placed_cards_node: Query<Entity, With<PlacedCardsNode>>,
-    scores_text: Single<Entity, With<ScoresText>>,
+    scores_text: Query<Entity, With<ScoresText>>,
+    screens: Query<
+        Entity,
+        Or<(
+            With<PassScreen>,
+            With<StatsScreen>,
+            With<BiddingPanel>,
+            With<JokerPopup>,
+            With<TrumpChooser>,
+            With<TrumpWidget>,
+            With<TrickBanner>,
+            With<LastTrickButton>,
+            With<LastTrickScreen>,
+            With<ScoreSheetScreen>,
+        )>,
+    >,
 ) {
-    for node in player_nodes {
+    for node in player_nodes.iter().chain(screens.iter()) {
         commands.entity(node).despawn();
     }
-    for node in placed_cards_node {
+    for node in placed_cards_node.iter().chain(scores_text.iter()) {
         commands.entity(node).despawn();
     }
-    commands.entity(*scores_text).despawn();
 
     commands.insert_resource(GameInfo::default());
+    commands.insert_resource(UndoHistory::default());
 }
 
+// back to the menu, the game is saved as it is and can be continued from there
+// Esc closes whatever's open over the table first
+#[allow(clippy::type_complexity)]
+fn leave_game(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    overlays: Query<
+        Entity,
+        Or<(
+            With<JokerPopup>,
+            With<StatsScreen>,
+            With<ScoreSheetScreen>,
+            With<LastTrickScreen>,
+        )>,
+    >,
+) {
+    if !overlays.is_empty() {
+        for entity in overlays {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    if !game_info.players.is_empty() {
+        save_game(&game_info);
+    }
+    commands.set_state(AppState::Menu);
+}
+
+// keeps a finished trick on the table for a while, or until a click, with the winner lit up
+#[allow(clippy::too_many_arguments)]
+fn hold_trick(
+    mut commands: Commands,
+    mut held: ResMut<TrickHeld>,
+    mut waited: Local<Option<f32>>,
+    mut placed: Query<(&mut Sprite, &Card), With<PlacedCard>>,
+    banner: Query<Entity, With<TrickBanner>>,
+    game_info: Res<GameInfo>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+) {
+    // the same check `award_scores` makes, the trick's done and all of it is on the table
+    let done = game_info.cards_placed.len() == 4 && placed.iter().count() == 4;
+
+    let Some(waited) = waited.as_mut().filter(|_| done) else {
+        // the trick was taken, undone or the game was left while it was held
+        held.0 = false;
+        *waited = None;
+        for entity in banner {
+            commands.entity(entity).despawn();
+        }
+        if done {
+            *waited = Some(0.0);
+            held.0 = true;
+
+            let winner = trick_winner(
+                &game_info.cards_placed,
+                game_info.trump,
+                game_info.joker_calls(),
+            );
+            for (mut sprite, card) in placed.iter_mut() {
+                if *card == winner {
+                    sprite.color = Color::srgb(1.0, 0.85, 0.4);
+                }
+            }
+            commands.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    top: Val::Percent(20.0),
+                    justify_content: JustifyContent::Center,
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                Pickable::IGNORE,
+                TrickBanner,
+                children![(
+                    Text::new(format!(
+                        "{} takes the trick{}",
+                        game_info.players[winner.player].name,
+                        if settings.trick_pause.is_none() {
+                            "\n(click to continue)"
+                        } else {
+                            ""
+                        }
+                    )),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    Pickable::IGNORE,
+                )],
+            ));
+        }
+        return;
+    };
+
+    *waited += time.delta_secs();
+    let clicked = mouse.just_pressed(MouseButton::Left) || touches.any_just_pressed();
+    if clicked || settings.trick_pause.is_some_and(|secs| *waited >= secs) {
+        held.0 = false;
+        for entity in banner {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn award_scores(
     mut commands: Commands,
     mut game_info: ResMut<GameInfo>,
+    mut profiles: ResMut<Profiles>,
+    mut current_match: Option<ResMut<Match>>,
     mut scores_text: Single<&mut Text, With<ScoresText>>,
-    query: Query<Entity, With<PlacedCard>>,
+    query: Query<(Entity, &Transform), With<PlacedCard>>,
     cards_in_hand: Query<(Entity, &Card), Without<PlacedCard>>,
+    settings: Res<Settings>,
+    viewer: Res<Viewer>,
+    held: Res<TrickHeld>,
+    layout: Res<Layout>,
 ) {
-    if game_info.cards_placed.len() == 4 {
-        let mut cards = game_info.cards_placed.iter().copied().collect::<Vec<_>>();
-        cards.sort_by_key(|card| std::cmp::Reverse(card.value(game_info.trump)));
-        let player = &mut game_info.players[cards[0].player];
-        player.taken += 1;
-        game_info.last_took = Some(cards[0].player);
+    if held.0 {
+        return;
+    }
+
+    // waits for the last card to be on the table too
+    if game_info.cards_placed.len() == 4 && query.iter().count() == 4 {
+        let winner = trick_winner(
+            &game_info.cards_placed,
+            game_info.trump,
+            game_info.joker_calls(),
+        )
+        .player;
+        game_info.players[winner].taken += 1;
+        game_info.last_took = Some(winner);
         game_info.last_cards_placed = game_info.cards_placed.clone();
-        for entity in query {
-            commands.entity(entity).despawn();
+        let trick = game_info.cards_placed.iter().rev().copied().collect();
+        if let Some(round) = game_info.rounds.last_mut() {
+            round.tricks.push(trick);
+        }
+        // the trick slides off to whoever took it
+        let position = viewer.table_position(winner, &game_info);
+        let to = Transform::from_translation(layout.hand_center(position))
+            .with_rotation(Quat::from_rotation_z(position as f32 * -90f32.to_radians()))
+            .with_scale(Vec3::ONE * layout.scale * 0.5);
+        for (entity, transform) in query {
+            match Tween::new(*transform, to, SWEEP_TIME, 0.0, &settings) {
+                Some(tween) => {
+                    commands
+                        .entity(entity)
+                        .remove::<(Card, PlacedCard)>()
+                        .insert(tween.despawning());
+                }
+                None => commands.entity(entity).despawn(),
+            }
         }
         game_info.cards_placed.clear();
+
+        // the last trick gets saved along with the next deal instead
+        if game_info
+            .players
+            .iter()
+            .any(|player| !player.cards.is_empty())
+        {
+            save_game(&game_info);
+        }
     }
 
     // round over
     if !game_info.players.is_empty() && cards_in_hand.is_empty() {
+        debug!(
+            "round {} deck seed: {}",
+            game_info.round,
+            game_info.seed.for_round(game_info.round)
+        );
         let h_penalty = game_info.h_penalty;
         for player in game_info.players.iter_mut() {
-            if player.taken == player.called {
-                player.score += (1 + player.taken) * 50
+            let points = if player.taken == player.called {
+                (1 + player.taken) * 50
             } else if player.taken == 0 {
-                player.score -= h_penalty;
+                -h_penalty
             } else {
-                player.score += player.taken * 10
-            }
+                player.taken * 10
+            };
+            player.score += points;
+            player.history.push(RoundScore {
+                called: player.called,
+                taken: player.taken,
+                points,
+            });
             player.taken = 0;
             player.called = 0;
             println!(
@@ -301,21 +722,54 @@ fn award_scores(
                 player.name, player.taken, player.called, player.score
             );
         }
+        let results = game_info
+            .players
+            .iter()
+            .map(|player| *player.history.last().unwrap())
+            .collect::<Vec<_>>();
+        if let Some(round) = game_info.rounds.last_mut() {
+            round.calls = results.iter().map(|result| result.called).collect();
+            round.points = results.iter().map(|result| result.points).collect();
+        }
         game_info.dealer = (game_info.dealer + 1) % 4;
         game_info.round += 1;
         game_info.last_took = None;
         game_info.last_cards_placed.clear();
 
         // game over
-        if game_info.round
-            == match game_info._type {
-                GameType::Classic => 24,
-                GameType::Nines => 16,
+        if game_info.round == game_info.rounds_total() {
+            let record = GameRecord::new(&game_info);
+            write_record(&record);
+            export_scores(&game_info, &record.file_stem());
+            let changes = profiles.record_game(&game_info);
+            profiles.save();
+            let ratings = game_info
+                .players
+                .iter()
+                .zip(changes)
+                .map(|(player, change)| (profiles.0[&player.name].rating, change))
+                .collect();
+
+            let mut standings = None;
+            if let Some(current_match) = &mut current_match {
+                current_match.add_game(&game_info);
+                standings = Some(current_match.to_text());
+                if current_match.is_over() {
+                    Match::delete();
+                    commands.remove_resource::<Match>();
+                } else {
+                    current_match.save();
+                }
             }
-        {
-            // TODO - game over screen, show scores
-            commands.run_system_cached(cleanup);
-            commands.run_system_cached(start_game);
+            delete_save();
+
+            commands.insert_resource(GameResult {
+                game_info: game_info.clone(),
+                record,
+                ratings,
+                standings,
+            });
+            commands.set_state(AppState::GameOver);
         } else {
             commands.run_system_cached(start_round);
         }
@@ -323,20 +777,28 @@ fn award_scores(
 
     // TODO - TEMP
     scores_text.0 = format!(
-        "Round: {}\nTrump: {:?}",
-        game_info.round,
-        game_info.trump.unwrap_or(Suit::Joker(false))
+        "Round: {}\n\nArrows/Enter: play\n0-9: call\nS: scores\nL: last trick",
+        game_info.round
     );
+    if let Some(current_match) = &current_match {
+        scores_text.0 += &format!("\n\n{}", current_match.to_text());
+    }
 }
 
 #[allow(clippy::type_complexity)]
 fn card_highlight(
     mut cards: Query<(&mut Sprite, &Card), Without<PlacedCard>>,
     game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
 ) {
     for (mut sprite, card) in cards.iter_mut() {
+        // greying out face down cards would give away what's in the hand,
+        // and nothing can be placed while calling, no point greying everything
         // if card.player == 0 {
-        sprite.color = if card.can_place(&game_info) {
+        sprite.color = if !viewer.can_see(&game_info.players[card.player], &game_info)
+            || game_info.calling()
+            || card.can_place(&game_info)
+        {
             Color::srgb(1.0, 1.0, 1.0)
         } else {
             Color::srgb(0.5, 0.5, 0.5)
@@ -345,27 +807,76 @@ fn card_highlight(
     }
 }
 
+// lays the table out again for the new window size
+// moves the cards to where the new window size puts them instead of rebuilding the table,
+// so a held trick's winner and a picked out card stay as they are
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn resize_event(
     mut commands: Commands,
-    mut cards: Query<(&mut Transform, &Card), Without<PlacedCard>>,
     mut resize_event: EventReader<WindowResized>,
+    mut layout: ResMut<Layout>,
+    mut cards: Query<(
+        &mut Transform,
+        &Card,
+        Has<PlacedCard>,
+        Option<&mut Tween>,
+        Option<&mut Dragged>,
+    )>,
+    mut badges: Query<&mut Transform, (With<JokerBadge>, Without<Card>)>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
 ) {
-    for event in resize_event.read() {
-        for (mut transform, card) in cards.iter_mut() {
-            transform.translation.y = if card.player % 2 == 0 {
-                (-event.height + CSH + 25.0) / 2.0
-            } else {
-                (-event.width + CSW + 25.0) / 2.0
-            };
+    if resize_event.read().last().is_none() || game_info.players.is_empty() {
+        return;
+    }
+    let old = *layout;
+    *layout = Layout::new(window.size(), game_info.cards_per_player());
+
+    // where `spawn_table` puts a card
+    let place = |layout: &Layout, card: &Card, placed: bool| {
+        let position = viewer.table_position(card.player, &game_info);
+        if placed {
+            return Some(layout.placed_card_transform(position));
+        }
+        let hand = &game_info.players[card.player].cards;
+        let j = hand.iter().position(|c| c == card)?;
+        Some(
+            Transform::from_xyz(
+                layout.hand_x(j, hand.len()),
+                layout.hand_y(position),
+                j as f32 * 0.01,
+            )
+            .with_scale(Vec3::ONE * layout.scale),
+        )
+    };
+
+    for (mut transform, card, placed, tween, dragged) in cards.iter_mut() {
+        let (Some(from), Some(to)) = (place(&old, card, placed), place(&layout, card, placed))
+        else {
+            continue;
+        };
+        if let Some(mut tween) = tween {
+            tween.to = to;
+        } else if let Some(mut dragged) = dragged {
+            dragged.home = to;
+        } else {
+            // a hovered or picked out card stays lifted
+            transform.translation += to.translation - from.translation;
+            transform.scale *= to.scale / from.scale;
         }
-        commands.run_system_cached(update_nametags);
     }
+    for mut transform in badges.iter_mut() {
+        transform.scale = Vec3::ONE / layout.scale;
+    }
+    commands.run_system_cached(update_nametags);
 }
 
 fn update_nametags(
     mut player_nametags: Query<(&mut Transform, &mut Text2d, &PlayerTag), Without<Card>>,
     game_info: Res<GameInfo>,
-    window: Single<&Window, With<PrimaryWindow>>,
+    viewer: Res<Viewer>,
+    layout: Res<Layout>,
 ) {
     for (mut transform, mut text, name) in player_nametags.iter_mut() {
         let (i, player) = game_info
@@ -379,17 +890,209 @@ fn update_nametags(
             name.0,
             if i == game_info.dealer { " (D)" } else { "" },
             player.taken,
-            player.called,
+            if game_info.has_called(i) {
+                player.called.to_string()
+            } else {
+                "-".to_string()
+            },
             player.score as f32 / 100.0
         );
-        transform.translation.y = if i % 2 == 0 {
-            (-window.height() + CSH + text.lines().count() as f32 * HALF_FONT_HEIGHT) / 2.0 + CSH
+        transform.translation.y = layout.nametag_y(viewer.table_position(i, &game_info), &text.0);
+    }
+}
+
+fn toggle_hot_seat(
+    mut commands: Commands,
+    mut viewer: ResMut<Viewer>,
+    pass_screen: Query<Entity, With<PassScreen>>,
+) {
+    viewer.hot_seat = !viewer.hot_seat;
+    viewer.show_hands = !viewer.hot_seat;
+    if viewer.hot_seat {
+        // makes follow_turn hand the device to whoever's turn it is
+        viewer.seat = None;
+        commands.run_system_cached(follow_turn);
+    } else {
+        for entity in pass_screen {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// in hot seat mode, hides the table until the next player has the device
+fn follow_turn(
+    mut commands: Commands,
+    mut viewer: ResMut<Viewer>,
+    game_info: Res<GameInfo>,
+    pass_screen: Query<Entity, With<PassScreen>>,
+) {
+    if !viewer.hot_seat || game_info.players.is_empty() {
+        return;
+    }
+
+    // the device stays with the last human while the bots play
+    let player = &game_info.players[game_info.current_player()];
+    let name = &player.name;
+    if player.bot || viewer.seat.as_ref() == Some(name) {
+        return;
+    }
+    viewer.seat = Some(name.clone());
+
+    for entity in pass_screen {
+        commands.entity(entity).despawn();
+    }
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::BLACK),
+            GlobalZIndex(i32::MAX),
+            PassScreen,
+            children![(
+                Text::new(format!("Pass the device to {name}\n\nclick to continue")),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Pickable::IGNORE,
+            )],
+        ))
+        .observe(|trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.entity(trigger.target).despawn();
+        });
+}
+
+// turns the table so the viewer sits at the bottom and flips the cards they can(n't) see
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn update_table_view(
+    mut commands: Commands,
+    mut player_nodes: Query<(&mut Transform, &PlayerNode, &Children)>,
+    mut nametags: Query<&mut Transform, (With<PlayerTag>, Without<PlayerNode>)>,
+    mut cards: Query<
+        (&mut Sprite, &mut Transform, &Card),
+        (Without<PlacedCard>, Without<PlayerNode>, Without<PlayerTag>),
+    >,
+    mut placed_cards: Query<
+        (&mut Transform, &Card),
+        (With<PlacedCard>, Without<PlayerNode>, Without<PlayerTag>),
+    >,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    assets: Res<CardAssets>,
+    layout: Res<Layout>,
+) {
+    if game_info.players.is_empty() {
+        return;
+    }
+
+    for (mut transform, node, children) in player_nodes.iter_mut() {
+        let position = viewer.table_position(node.0, &game_info);
+        transform.rotation = Quat::from_rotation_z(position as f32 * -90f32.to_radians());
+
+        for child in children {
+            if let Ok(mut transform) = nametags.get_mut(*child) {
+                transform.rotation = Quat::from_rotation_z(position as f32 * 90f32.to_radians());
+            }
+        }
+    }
+
+    for (mut sprite, mut transform, card) in cards.iter_mut() {
+        sprite.image = if viewer.can_see(&game_info.players[card.player], &game_info) {
+            assets.primary[&card.as_string()].clone()
         } else {
-            (-window.width()
-                + CSW
-                + text.lines().map(|line| line.len()).max().unwrap() as f32 * HALF_FONT_HEIGHT)
-                / 2.0
-                + CSW
+            assets.extra["back"].clone()
+        };
+        transform.translation.y = layout.hand_y(viewer.table_position(card.player, &game_info));
+    }
+
+    for (mut transform, card) in placed_cards.iter_mut() {
+        *transform = layout.placed_card_transform(viewer.table_position(card.player, &game_info));
+    }
+
+    commands.run_system_cached(update_nametags);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // a finished trick on the table, the way `spawn_table` leaves it
+    fn finish_trick(world: &mut World, codes: [&str; 4]) {
+        let cards = codes.map(|code| Card::from_string(code.to_string(), 0));
+        for (seat, mut card) in cards.into_iter().enumerate() {
+            card.player = seat;
+            world
+                .resource_mut::<GameInfo>()
+                .cards_placed
+                .push_front(card);
+            world.spawn((Sprite::default(), card, PlacedCard));
+        }
+    }
+
+    // what `award_scores` does once the trick is let go
+    fn take_trick(world: &mut World) {
+        world.resource_mut::<GameInfo>().cards_placed.clear();
+        let placed = world
+            .query_filtered::<Entity, With<PlacedCard>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        for entity in placed {
+            world.despawn(entity);
+        }
+    }
+
+    #[test]
+    fn every_trick_is_held() {
+        let mut world = World::new();
+        let mut game_info = GameInfo::default();
+        for name in PLAYER_NAMES {
+            game_info.players.push_back(Player {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+        world.insert_resource(game_info);
+        world.insert_resource(Settings {
+            trick_pause: Some(1.0),
+            ..Default::default()
+        });
+        world.init_resource::<TrickHeld>();
+        world.init_resource::<Time>();
+        world.init_resource::<ButtonInput<MouseButton>>();
+        world.init_resource::<Touches>();
+        let hold_trick = world.register_system(hold_trick);
+
+        // the ace takes both, the first one's from the second seat
+        for (codes, winner) in [
+            (["S7", "SA", "S8", "S9"], "SA"),
+            (["HA", "H6", "H7", "H8"], "HA"),
+        ] {
+            finish_trick(&mut world, codes);
+            world.run_system(hold_trick).unwrap();
+            assert!(world.resource::<TrickHeld>().0);
+            let mut banner = world.query_filtered::<(), With<TrickBanner>>();
+            assert_eq!(banner.iter(&world).count(), 1);
+            let mut sprites = world.query::<(&Sprite, &Card)>();
+            let lit = sprites
+                .iter(&world)
+                .filter(|(sprite, _)| sprite.color != Color::WHITE)
+                .map(|(_, card)| card.as_string())
+                .collect::<Vec<_>>();
+            assert_eq!(lit, [winner]);
+
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(2));
+            world.run_system(hold_trick).unwrap();
+            assert!(!world.resource::<TrickHeld>().0);
+
+            take_trick(&mut world);
+            world.run_system(hold_trick).unwrap();
         }
     }
 }
@@ -0,0 +1,399 @@
+use bevy::{
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+
+use crate::{
+    button,
+    components::*,
+    consts::{ANIMATION_SPEEDS, H_PENALTIES, MATCH_LENGTHS, MAX_NAME_LENGTH, TRICK_PAUSES},
+    profiles::toggle_stats_screen,
+    save::{delete_save, has_save},
+    settings::Settings,
+    skins::Skins,
+    tournament::Match,
+};
+
+// the seat whose name is being typed in
+#[derive(Resource, Default)]
+pub struct EditingName(pub Option<usize>);
+
+fn row() -> Node {
+    Node {
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(10.0),
+        ..Default::default()
+    }
+}
+
+fn label(text: impl Into<String>) -> impl Bundle {
+    (
+        Text::new(text),
+        Node {
+            width: Val::Px(120.0),
+            ..Default::default()
+        },
+    )
+}
+
+// rebuilt from scratch whenever the setup changes, it's small enough
+pub fn spawn_menu(
+    mut commands: Commands,
+    setup: Res<GameSetup>,
+    settings: Res<Settings>,
+    skins: Res<Skins>,
+    editing: Res<EditingName>,
+    current_match: Option<Res<Match>>,
+    screen: Query<Entity, With<MenuScreen>>,
+) {
+    for entity in screen {
+        commands.entity(entity).despawn();
+    }
+
+    let mut menu = commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(4.0),
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        },
+        MenuScreen,
+    ));
+    menu.with_child((
+        Text::new("Joker"),
+        TextFont::from_font_size(48.0),
+        Node {
+            margin: UiRect::bottom(Val::Px(20.0)),
+            ..Default::default()
+        },
+    ));
+
+    menu.with_children(|menu| {
+        if has_save() {
+            menu.spawn(button("Continue saved game")).observe(
+                |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                    commands.set_state(AppState::Playing);
+                },
+            );
+        } else if let Some(current_match) = &current_match {
+            // between games of a match, the next one gets seated by the match
+            menu.spawn(button(&format!(
+                "Continue match (game {}/{})",
+                current_match.results.len() + 1,
+                current_match.games_total
+            )))
+            .observe(|_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                commands.insert_resource(GameInfo::default());
+                commands.set_state(AppState::Playing);
+            });
+        }
+
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("Game type"));
+            row.spawn(button(&format!("{:?}", setup.game_type)))
+                .observe(|_: Trigger<Pointer<Click>>, mut setup: ResMut<GameSetup>| {
+                    setup.game_type = match setup.game_type {
+                        GameType::Classic => GameType::Nines,
+                        GameType::Nines => GameType::Classic,
+                    };
+                });
+        });
+
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("H penalty"));
+            row.spawn(button(&format!("{:.2}", setup.h_penalty as f32 / 100.0)))
+                .observe(|_: Trigger<Pointer<Click>>, mut setup: ResMut<GameSetup>| {
+                    let next = H_PENALTIES
+                        .iter()
+                        .position(|&penalty| penalty == setup.h_penalty)
+                        .map_or(0, |i| (i + 1) % H_PENALTIES.len());
+                    setup.h_penalty = H_PENALTIES[next];
+                });
+        });
+
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("Match"));
+            row.spawn(button(&match setup.games {
+                1 => "single game".to_string(),
+                games => format!("{games} games"),
+            }))
+            .observe(|_: Trigger<Pointer<Click>>, mut setup: ResMut<GameSetup>| {
+                let next = MATCH_LENGTHS
+                    .iter()
+                    .position(|&games| games == setup.games)
+                    .map_or(0, |i| (i + 1) % MATCH_LENGTHS.len());
+                setup.games = MATCH_LENGTHS[next];
+            });
+            if setup.games > 1 {
+                row.spawn(button(&format!(
+                    "duplicate: {}",
+                    if setup.duplicate { "on" } else { "off" }
+                )))
+                .observe(
+                    |_: Trigger<Pointer<Click>>, mut setup: ResMut<GameSetup>| {
+                        setup.duplicate = !setup.duplicate;
+                    },
+                );
+            }
+        });
+
+        for seat in 0..4 {
+            menu.spawn(row()).with_children(|row| {
+                row.spawn(label(format!("Seat {}", seat + 1)));
+
+                let name = &setup.names[seat];
+                let (name, border) = if editing.0 == Some(seat) {
+                    (format!("{name}_"), Color::WHITE)
+                } else {
+                    (name.clone(), Color::srgb(0.4, 0.4, 0.4))
+                };
+                row.spawn((
+                    Node {
+                        width: Val::Px(200.0),
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(10.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..Default::default()
+                    },
+                    BorderColor(border),
+                    children![(Text::new(name), Pickable::IGNORE)],
+                ))
+                .observe(
+                    move |_: Trigger<Pointer<Click>>, mut editing: ResMut<EditingName>| {
+                        editing.0 = if editing.0 == Some(seat) {
+                            None
+                        } else {
+                            Some(seat)
+                        };
+                    },
+                );
+
+                row.spawn(button(if setup.bots[seat] { "Bot" } else { "Human" }))
+                    .observe(
+                        move |_: Trigger<Pointer<Click>>, mut setup: ResMut<GameSetup>| {
+                            setup.bots[seat] = !setup.bots[seat];
+                        },
+                    );
+            });
+        }
+
+        if let Some(problem) = setup.problem() {
+            menu.spawn((Text::new(problem), TextColor(Color::srgb(1.0, 0.3, 0.3))));
+        }
+
+        menu.spawn(button(if current_match.is_some() {
+            "New game, gives up the match (Enter)"
+        } else {
+            "New game (Enter)"
+        }))
+        .observe(|_: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.run_system_cached(start_new_game);
+        });
+
+        // TODO - there's no server to join yet
+        menu.spawn((
+            Node {
+                padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+                margin: UiRect::all(Val::Px(10.0)),
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            children![(
+                Text::new("Join network game (not available yet)"),
+                TextColor(Color::srgb(0.5, 0.5, 0.5)),
+            )],
+        ));
+
+        menu.spawn(button("Statistics")).observe(
+            |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                commands.run_system_cached(toggle_stats_screen);
+            },
+        );
+
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("Settings"));
+            row.spawn(button(&format!(
+                "Show everyone's cards: {}",
+                if settings.show_hands { "on" } else { "off" }
+            )))
+            .observe(
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    settings.show_hands = !settings.show_hands;
+                    settings.save();
+                },
+            );
+        });
+
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("Animations"));
+            row.spawn(button(if settings.animations { "on" } else { "off" }))
+                .observe(
+                    |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                        settings.animations = !settings.animations;
+                        settings.save();
+                    },
+                );
+            row.spawn(button(&format!("speed {}x", settings.animation_speed)))
+                .observe(
+                    |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                        let next = ANIMATION_SPEEDS
+                            .iter()
+                            .position(|&speed| speed == settings.animation_speed)
+                            .map_or(1, |i| (i + 1) % ANIMATION_SPEEDS.len());
+                        settings.animation_speed = ANIMATION_SPEEDS[next];
+                        settings.save();
+                    },
+                );
+        });
+
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("Trick pause"));
+            row.spawn(button(&match settings.trick_pause {
+                Some(secs) => format!("{secs}s"),
+                None => "until click".to_string(),
+            }))
+            .observe(
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    let next = TRICK_PAUSES
+                        .iter()
+                        .position(|&pause| pause == settings.trick_pause)
+                        .map_or(0, |i| (i + 1) % TRICK_PAUSES.len());
+                    settings.trick_pause = TRICK_PAUSES[next];
+                    settings.save();
+                },
+            );
+        });
+
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("Tap to confirm"));
+            row.spawn(button(if settings.tap_to_confirm { "on" } else { "off" }))
+                .observe(
+                    |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                        settings.tap_to_confirm = !settings.tap_to_confirm;
+                        settings.save();
+                    },
+                );
+        });
+
+        // what's asked for in the settings might not be there anymore
+        let (skin, back) = skins.pick(&settings.skin, &settings.back);
+        menu.spawn(row()).with_children(|row| {
+            row.spawn(label("Deck"));
+            row.spawn(button(&skin.name)).observe(
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>, skins: Res<Skins>| {
+                    let current = skins.pick(&settings.skin, &settings.back).0;
+                    let next = skins
+                        .skins
+                        .iter()
+                        .position(|skin| skin.name == current.name)
+                        .map_or(0, |i| (i + 1) % skins.skins.len());
+                    let Some(next) = skins.skins.get(next) else {
+                        return;
+                    };
+                    settings.skin = next.name.clone();
+                    if !next.backs.contains(&settings.back) {
+                        settings.back = next.backs[0].clone();
+                    }
+                    settings.save();
+                },
+            );
+            row.spawn(button(&format!("back {back}"))).observe(
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>, skins: Res<Skins>| {
+                    let (skin, back) = skins.pick(&settings.skin, &settings.back);
+                    let next = skin.backs.iter().position(|b| *b == back).unwrap_or(0);
+                    settings.skin = skin.name.clone();
+                    settings.back = skin.backs[(next + 1) % skin.backs.len()].clone();
+                    settings.save();
+                },
+            );
+        });
+        for problem in &skins.problems {
+            menu.spawn((
+                Text::new(problem),
+                TextFont::from_font_size(14.0),
+                TextColor(Color::srgb(1.0, 0.3, 0.3)),
+            ));
+        }
+
+        menu.spawn(button("Quit")).observe(
+            |_: Trigger<Pointer<Click>>, mut exit: EventWriter<AppExit>| {
+                exit.write(AppExit::Success);
+            },
+        );
+    });
+}
+
+// a new game or match from here gives up whatever was going on
+pub fn start_new_game(mut commands: Commands, setup: Res<GameSetup>) {
+    if setup.problem().is_some() {
+        return;
+    }
+    delete_save();
+    Match::delete();
+    commands.remove_resource::<Match>();
+    if setup.games > 1 {
+        let names = setup.names.each_ref().map(String::as_str);
+        let new_match = Match::new(&names, &setup.bots, setup.games, setup.duplicate);
+        new_match.save();
+        commands.insert_resource(new_match);
+    }
+    commands.insert_resource(GameInfo::default());
+    commands.set_state(AppState::Playing);
+}
+
+pub fn exit_menu(
+    mut commands: Commands,
+    mut editing: ResMut<EditingName>,
+    screen: Query<Entity, With<MenuScreen>>,
+) {
+    editing.0 = None;
+    for entity in screen {
+        commands.entity(entity).despawn();
+    }
+}
+
+// typing into the name field that was clicked, Enter or Esc finish it
+pub fn edit_name(
+    mut keys: EventReader<KeyboardInput>,
+    mut editing: ResMut<EditingName>,
+    mut setup: ResMut<GameSetup>,
+) {
+    let Some(seat) = editing.0 else {
+        keys.clear();
+        return;
+    };
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+        match &key.logical_key {
+            Key::Enter | Key::Escape => {
+                let name = setup.names[seat].trim().to_string();
+                setup.names[seat] = name;
+                editing.0 = None;
+                return;
+            }
+            Key::Backspace => {
+                setup.names[seat].pop();
+            }
+            Key::Space if setup.names[seat].chars().count() < MAX_NAME_LENGTH => {
+                setup.names[seat].push(' ');
+            }
+            Key::Character(text) => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if setup.names[seat].chars().count() < MAX_NAME_LENGTH {
+                        setup.names[seat].push(c);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::{collections::BTreeMap, fs};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{GameInfo, GameType, StatsScreen},
+    consts::{INITIAL_RATING, PROFILES_FILE},
+    rating::rating_changes,
+    save::{data_path, write_data},
+};
+
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+pub struct GameStats {
+    pub games: u32,
+    pub wins: u32,
+    pub total_score: i64,
+}
+
+impl GameStats {
+    fn add(&mut self, won: bool, score: i32) {
+        self.games += 1;
+        self.wins += u32::from(won);
+        self.total_score += i64::from(score);
+    }
+
+    pub fn average_score(&self) -> f32 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.total_score as f32 / self.games as f32
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default = "initial_rating")]
+    pub rating: f64,
+    pub overall: GameStats,
+    pub by_seat: [GameStats; 4],
+    pub by_game_type: BTreeMap<GameType, GameStats>,
+    pub rounds: u32,
+    // rounds where taken == called
+    pub exact_calls: u32,
+    pub h_penalties: u32,
+}
+
+fn initial_rating() -> f64 {
+    INITIAL_RATING
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            overall: GameStats::default(),
+            by_seat: Default::default(),
+            by_game_type: BTreeMap::new(),
+            rounds: 0,
+            exact_calls: 0,
+            h_penalties: 0,
+        }
+    }
+}
+
+impl Profile {
+    pub fn call_accuracy(&self) -> f32 {
+        if self.rounds == 0 {
+            0.0
+        } else {
+            self.exact_calls as f32 / self.rounds as f32
+        }
+    }
+}
+
+// everyone who ever played on this machine, by name
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Profiles(pub BTreeMap<String, Profile>);
+
+impl Profiles {
+    pub fn load() -> Self {
+        let path = data_path(PROFILES_FILE);
+        let Ok(profiles) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&profiles).unwrap_or_else(|err| {
+            warn!("ignoring broken profiles {}: {err}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = write_data(PROFILES_FILE, &serde_json::to_string_pretty(self).unwrap()) {
+            warn!("couldn't save the player profiles: {err}");
+        }
+    }
+
+    // updates the ratings of `players` by the final `scores`, returns the changes by seat
+    pub fn rate_game(&mut self, players: &[String], scores: &[i32]) -> Vec<f64> {
+        let ratings = players
+            .iter()
+            .map(|name| self.0.get(name).map_or(INITIAL_RATING, |p| p.rating))
+            .collect::<Vec<_>>();
+        let changes = rating_changes(&ratings, scores);
+        for (name, change) in players.iter().zip(&changes) {
+            self.0.entry(name.clone()).or_default().rating += change;
+        }
+        changes
+    }
+
+    // adds a finished game to everyone's profile, all players tied for the top score win
+    // returns the rating changes by seat
+    pub fn record_game(&mut self, game_info: &GameInfo) -> Vec<f64> {
+        let best = game_info.players.iter().map(|p| p.score).max().unwrap_or(0);
+
+        for (seat, player) in game_info.players.iter().enumerate() {
+            let profile = self.0.entry(player.name.clone()).or_default();
+            let won = player.score == best;
+
+            profile.overall.add(won, player.score);
+            profile.by_seat[seat].add(won, player.score);
+            profile
+                .by_game_type
+                .entry(game_info._type)
+                .or_default()
+                .add(won, player.score);
+
+            for round in &player.history {
+                profile.rounds += 1;
+                profile.exact_calls += u32::from(round.taken == round.called);
+                profile.h_penalties += u32::from(round.taken == 0 && round.called != 0);
+            }
+        }
+
+        let players = game_info
+            .players
+            .iter()
+            .map(|p| p.name.clone())
+            .collect::<Vec<_>>();
+        let scores = game_info
+            .players
+            .iter()
+            .map(|p| p.score)
+            .collect::<Vec<_>>();
+        self.rate_game(&players, &scores)
+    }
+
+    fn to_text(&self) -> String {
+        if self.0.is_empty() {
+            return "No finished games yet".to_string();
+        }
+
+        let mut text = String::new();
+        for (name, profile) in &self.0 {
+            let overall = profile.overall;
+            text += &format!(
+                "{name} ({:.0})\n  games {}, wins {}, average score {:.2}\n  exact calls {:.0}%, H penalties {}\n",
+                profile.rating,
+                overall.games,
+                overall.wins,
+                overall.average_score() / 100.0,
+                profile.call_accuracy() * 100.0,
+                profile.h_penalties,
+            );
+            for (seat, stats) in profile.by_seat.iter().enumerate() {
+                if stats.games > 0 {
+                    text += &format!(
+                        "  seat {}: {} games, {} wins, average {:.2}\n",
+                        seat + 1,
+                        stats.games,
+                        stats.wins,
+                        stats.average_score() / 100.0
+                    );
+                }
+            }
+            for (game_type, stats) in &profile.by_game_type {
+                text += &format!(
+                    "  {game_type:?}: {} games, {} wins, average {:.2}\n",
+                    stats.games,
+                    stats.wins,
+                    stats.average_score() / 100.0
+                );
+            }
+            text += "\n";
+        }
+        text
+    }
+}
+
+pub fn toggle_stats_screen(
+    mut commands: Commands,
+    profiles: Res<Profiles>,
+    stats_screen: Query<Entity, With<StatsScreen>>,
+) {
+    if !stats_screen.is_empty() {
+        for entity in stats_screen {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(20.0)),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::scroll_y(),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            GlobalZIndex(i32::MAX - 1),
+            StatsScreen,
+            children![
+                (Text::new("Statistics (click to close)\n"), Pickable::IGNORE),
+                (Text::new(profiles.to_text()), Pickable::IGNORE),
+            ],
+        ))
+        .observe(|trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.entity(trigger.target).despawn();
+        });
+}
@@ -0,0 +1,233 @@
+// Messages exchanged between a server and its clients, see PROTOCOL.md for the wire format.
+// nothing speaks it in this crate yet, it's here for servers and third-party clients
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    card::{Card, JokerCall, Suit},
+    components::GameType,
+    deck::Seed,
+};
+
+// bump this whenever a message changes shape
+pub const PROTOCOL_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    // has to be the first message on a connection
+    Hello {
+        version: u32,
+        name: String,
+    },
+    Call {
+        called: i32,
+    },
+    // a joker has to say what it's played as
+    PlaceCard {
+        card: Card,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        joker: Option<JokerCall>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        version: u32,
+        seat: usize,
+    },
+    // the server closes the connection after sending this
+    Rejected {
+        reason: String,
+    },
+    GameStarted {
+        game_type: GameType,
+        players: Vec<String>,
+        h_penalty: i32,
+    },
+    // only ever contains the receiving player's own hand,
+    // `commitment` is the hash of the seed the deck was shuffled with
+    RoundStarted {
+        round: usize,
+        dealer: usize,
+        trump: Option<Suit>,
+        hand: Vec<Card>,
+        commitment: String,
+    },
+    Called {
+        seat: usize,
+        called: i32,
+    },
+    CardPlaced {
+        card: Card,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        joker: Option<JokerCall>,
+    },
+    TrickTaken {
+        seat: usize,
+    },
+    // reveals the round's seed so the deal can be checked with `verify_deal`
+    RoundOver {
+        scores: Vec<i32>,
+        seed: Seed,
+    },
+    GameOver {
+        scores: Vec<i32>,
+    },
+}
+
+/// The server's answer to the first message on a connection.
+pub fn handshake(message: &ClientMessage, seat: usize) -> ServerMessage {
+    match message {
+        ClientMessage::Hello { version, .. } if *version == PROTOCOL_VERSION => {
+            ServerMessage::Welcome {
+                version: PROTOCOL_VERSION,
+                seat,
+            }
+        }
+        ClientMessage::Hello { version, .. } => ServerMessage::Rejected {
+            reason: format!(
+                "protocol version mismatch, server is on {PROTOCOL_VERSION} and client is on {version}"
+            ),
+        },
+        _ => ServerMessage::Rejected {
+            reason: "expected a hello message".to_string(),
+        },
+    }
+}
+
+pub fn encode<T: Serialize>(message: &T) -> String {
+    // none of the messages have anything serde_json can't represent
+    serde_json::to_string(message).unwrap()
+}
+
+pub fn decode<T: DeserializeOwned>(line: &str) -> serde_json::Result<T> {
+    serde_json::from_str(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Rank;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(message: T) {
+        assert_eq!(decode::<T>(&encode(&message)).unwrap(), message);
+    }
+
+    #[test]
+    fn client_messages_round_trip() {
+        round_trip(ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name: "giorgi".to_string(),
+        });
+        round_trip(ClientMessage::Call { called: 2 });
+        round_trip(ClientMessage::PlaceCard {
+            card: Card::from_string("JB".to_string(), 3),
+            joker: Some(JokerCall::Highest(Suit::Clubs)),
+        });
+    }
+
+    #[test]
+    fn server_messages_round_trip() {
+        round_trip(ServerMessage::Welcome {
+            version: PROTOCOL_VERSION,
+            seat: 1,
+        });
+        round_trip(ServerMessage::Rejected {
+            reason: "full".to_string(),
+        });
+        round_trip(ServerMessage::GameStarted {
+            game_type: GameType::Nines,
+            players: vec!["lela".to_string(), "ilia".to_string()],
+            h_penalty: 200,
+        });
+        round_trip(ServerMessage::RoundStarted {
+            round: 4,
+            dealer: 3,
+            trump: Some(Suit::Hearts),
+            hand: ["SA", "D1", "JR"]
+                .iter()
+                .map(|code| Card::from_string(code.to_string(), 0))
+                .collect(),
+            commitment: Seed::random().commitment(),
+        });
+        round_trip(ServerMessage::RoundStarted {
+            round: 0,
+            dealer: 0,
+            trump: None,
+            hand: vec![],
+            commitment: String::new(),
+        });
+        round_trip(ServerMessage::Called { seat: 2, called: 0 });
+        round_trip(ServerMessage::CardPlaced {
+            card: Card::from_string("C7".to_string(), 2),
+            joker: None,
+        });
+        round_trip(ServerMessage::CardPlaced {
+            card: Card::from_string("JR".to_string(), 1),
+            joker: Some(JokerCall::Pass),
+        });
+        round_trip(ServerMessage::TrickTaken { seat: 0 });
+        round_trip(ServerMessage::RoundOver {
+            scores: vec![100, -200, 20, 0],
+            seed: Seed::random(),
+        });
+        round_trip(ServerMessage::GameOver {
+            scores: vec![1000, 950, -400, 30],
+        });
+    }
+
+    #[test]
+    fn cards_use_compact_codes() {
+        let card = Card::from_string("H1".to_string(), 2);
+        assert_eq!(encode(&card), "\"H12\"");
+        assert_eq!(
+            decode::<Card>("\"H12\"").unwrap(),
+            Card {
+                rank: Rank::Ten,
+                suit: Suit::Hearts,
+                player: 2
+            }
+        );
+        assert_eq!(
+            encode(&ClientMessage::PlaceCard { card, joker: None }),
+            r#"{"type":"place_card","card":"H12"}"#
+        );
+    }
+
+    #[test]
+    fn invalid_cards_are_rejected() {
+        assert!(decode::<Card>("\"S6\"").is_err()); // not in a joker deck
+        assert!(decode::<Card>("\"XX1\"").is_err());
+        assert!(decode::<Card>("\"SA\"").is_err());
+        assert!(decode::<Card>("\"S\"").is_err());
+        assert!(decode::<Card>("\"SA4\"").is_err());
+        assert!(decode::<Card>("\"SA99\"").is_err());
+    }
+
+    #[test]
+    fn handshake_checks_version() {
+        let hello = |version| ClientMessage::Hello {
+            version,
+            name: "lizi".to_string(),
+        };
+        assert_eq!(
+            handshake(&hello(PROTOCOL_VERSION), 2),
+            ServerMessage::Welcome {
+                version: PROTOCOL_VERSION,
+                seat: 2
+            }
+        );
+        assert!(matches!(
+            handshake(&hello(PROTOCOL_VERSION + 1), 2),
+            ServerMessage::Rejected { .. }
+        ));
+        assert!(matches!(
+            handshake(&ClientMessage::Call { called: 1 }, 2),
+            ServerMessage::Rejected { .. }
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+/*
+    Four player free-for-all Elo.
+    A finished game counts as six head to head matches, one for every pair of players,
+    the one with more points wins it and equal points are a draw.
+    Each match is worth K / 3, so a player beating everyone moves by at most K in total.
+*/
+
+use std::{fs, path::Path};
+
+use crate::{
+    consts::{INITIAL_RATING, RATING_K},
+    profiles::Profiles,
+    record::read_record,
+};
+
+// how much each player's rating moves, by seat
+pub fn rating_changes(ratings: &[f64], scores: &[i32]) -> Vec<f64> {
+    let matches = ratings.len().saturating_sub(1).max(1) as f64;
+    let mut changes = vec![0.0; ratings.len()];
+
+    for i in 0..ratings.len() {
+        for j in 0..ratings.len() {
+            if i == j {
+                continue;
+            }
+            let expected = 1.0 / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+            let actual = match scores[i].cmp(&scores[j]) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+            changes[i] += RATING_K / matches * (actual - expected);
+        }
+    }
+
+    changes
+}
+
+// `cargo run -- --recompute-ratings <dir>`
+// throws away every rating and replays all the finished games in `dir` in the order they were played
+pub fn recompute_ratings(dir: &Path) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut records = vec![];
+    for path in paths {
+        match read_record(&path) {
+            Ok(record) => records.push(record),
+            Err(err) => eprintln!("skipping {err}"),
+        }
+    }
+    // older records without a time keep the file order within their day
+    records.sort_by(|a, b| (&a.date, &a.time).cmp(&(&b.date, &b.time)));
+
+    let mut profiles = Profiles::load();
+    for profile in profiles.0.values_mut() {
+        profile.rating = INITIAL_RATING;
+    }
+
+    let mut games = 0;
+    for record in &records {
+        let finished = record.rounds.len() == record.game_type.rounds_total()
+            && record.rounds.iter().all(|round| round.points.len() == 4);
+        if !finished {
+            continue;
+        }
+
+        let scores = (0..4)
+            .map(|seat| record.rounds.iter().map(|round| round.points[seat]).sum())
+            .collect::<Vec<i32>>();
+        profiles.rate_game(&record.players, &scores);
+        games += 1;
+    }
+
+    profiles.save();
+    println!(
+        "rated {games} finished games out of {} records",
+        records.len()
+    );
+    for (name, profile) in &profiles.0 {
+        println!("{name}: {:.0}", profile.rating);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratings_are_zero_sum() {
+        let changes = rating_changes(&[1500.0, 1600.0, 1400.0, 1550.0], &[3000, 1200, 1200, -400]);
+        assert!(changes.iter().sum::<f64>().abs() < 1e-9);
+        assert!(changes[0] > 0.0);
+        assert!(changes[3] < 0.0);
+        // a draw between equal ratings changes nothing
+        assert_eq!(rating_changes(&[1500.0; 4], &[100; 4]), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn beating_everyone_is_worth_at_most_k() {
+        let changes = rating_changes(&[1000.0, 2000.0, 2000.0, 2000.0], &[1, 0, 0, 0]);
+        assert!(changes[0] <= RATING_K && changes[0] > RATING_K * 0.9);
+    }
+}
@@ -0,0 +1,497 @@
+/*
+    Game records are plain text, loosely modelled on chess PGN.
+    A header of tags comes first, then a blank line and every round in order:
+
+    [Event "Joker"]
+    [Date "2026.10.19"]
+    [Time "13:05:42.120"]
+    [Seat0 "giorgi"]
+    [Seat1 "lela"]
+    [Seat2 "ilia"]
+    [Seat3 "lizi"]
+    [GameType "Classic"]
+    [Ruleset "HPenalty=200"]
+    [Seed "<64 hex digits>"]
+
+    Round 1 Dealer 3 Trump H
+    Deal 0 SA0 D70
+    Deal 1 S91 JR1
+    Deal 2 DK2 H62
+    Deal 3 C73 HA3
+    Calls 1 0 1 0
+    Trick SA0 S91 DK2 C73
+    Trick D70 JR1 H62 HA3
+    Points 100 10 -200 50
+
+    Cards are written like on the wire (see PROTOCOL.md), the asset code followed by the seat.
+    Tricks list the cards in the order they were played, the trump is `S`, `D`, `C`, `H` or `-`.
+    A joker in a trick is followed by how it was played, `+` wants the trick and `-` gives it away:
+    `JR0+S` highest spades, `JR0-S` spades take it, `JB2+` take, `JB2-` pass.
+    The date and time are when the game finished, in UTC.
+*/
+
+use std::{fmt::Write, fs, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    card::{Card, JokerCall, Suit},
+    components::{GameInfo, GameType},
+    deck::Seed,
+    protocol,
+    save::write_data,
+};
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub dealer: usize,
+    pub trump: Option<Suit>,
+    pub hands: Vec<Vec<Card>>,
+    pub calls: Vec<i32>,
+    pub tricks: Vec<Vec<Card>>,
+    pub points: Vec<i32>,
+    #[serde(default)]
+    pub joker_calls: Vec<(Card, JokerCall)>,
+}
+
+pub struct GameRecord {
+    pub date: String,
+    // down to the millisecond, games finished on the same day are put in order by it
+    pub time: String,
+    pub players: Vec<String>,
+    pub game_type: GameType,
+    pub h_penalty: i32,
+    pub seed: Seed,
+    pub rounds: Vec<RoundRecord>,
+}
+
+impl GameRecord {
+    pub fn new(game_info: &GameInfo) -> Self {
+        let (date, time) = now();
+        Self {
+            date,
+            time,
+            players: game_info.players.iter().map(|p| p.name.clone()).collect(),
+            game_type: game_info._type,
+            h_penalty: game_info.h_penalty,
+            seed: game_info.seed,
+            rounds: game_info.rounds.clone(),
+        }
+    }
+
+    // games in a duplicate match share a seed, so files are named after when the game finished
+    pub fn file_stem(&self) -> String {
+        format!("{}-{}", self.date, self.time).replace(['.', ':'], "")
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "[Event \"Joker\"]");
+        let _ = writeln!(text, "[Date \"{}\"]", self.date);
+        let _ = writeln!(text, "[Time \"{}\"]", self.time);
+        for (i, name) in self.players.iter().enumerate() {
+            let _ = writeln!(text, "[Seat{i} \"{name}\"]");
+        }
+        let _ = writeln!(text, "[GameType \"{:?}\"]", self.game_type);
+        let _ = writeln!(text, "[Ruleset \"HPenalty={}\"]", self.h_penalty);
+        let _ = writeln!(text, "[Seed \"{}\"]", self.seed);
+
+        for (i, round) in self.rounds.iter().enumerate() {
+            let _ = writeln!(
+                text,
+                "\nRound {} Dealer {} Trump {}",
+                i + 1,
+                round.dealer,
+                match round.trump {
+                    Some(Suit::Spades) => "S",
+                    Some(Suit::Diamonds) => "D",
+                    Some(Suit::Clubs) => "C",
+                    Some(Suit::Hearts) => "H",
+                    Some(Suit::Joker(_)) | None => "-",
+                }
+            );
+            for (seat, hand) in round.hands.iter().enumerate() {
+                let _ = writeln!(text, "Deal {seat} {}", cards_to_text(hand));
+            }
+            let _ = writeln!(text, "Calls {}", numbers_to_text(&round.calls));
+            for trick in &round.tricks {
+                let _ = writeln!(text, "Trick {}", trick_to_text(trick, &round.joker_calls));
+            }
+            if !round.points.is_empty() {
+                let _ = writeln!(text, "Points {}", numbers_to_text(&round.points));
+            }
+        }
+
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut record = Self {
+            date: String::new(),
+            time: String::new(),
+            players: vec![],
+            game_type: GameType::Classic,
+            h_penalty: 0,
+            seed: Seed([0; 32]),
+            rounds: vec![],
+        };
+
+        let mut seats: [Option<String>; 4] = Default::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let error = |msg: &str| format!("line {}: {msg}: `{line}`", number + 1);
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(tag) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let (name, value) = tag
+                    .split_once(' ')
+                    .ok_or_else(|| error("tag without a value"))?;
+                let value = value.trim_matches('"');
+                match name {
+                    "Date" => record.date = value.to_string(),
+                    "Time" => record.time = value.to_string(),
+                    "GameType" => {
+                        record.game_type = match value {
+                            "Classic" => GameType::Classic,
+                            "Nines" => GameType::Nines,
+                            _ => return Err(error("unknown game type")),
+                        }
+                    }
+                    "Ruleset" => {
+                        for rule in value.split(',') {
+                            if let Some(penalty) = rule.trim().strip_prefix("HPenalty=") {
+                                record.h_penalty =
+                                    penalty.parse().map_err(|_| error("bad H penalty"))?;
+                            }
+                        }
+                    }
+                    "Seed" => record.seed = value.parse()?,
+                    _ if name.starts_with("Seat") => {
+                        let Ok(seat @ 0..4) = name["Seat".len()..].parse::<usize>() else {
+                            return Err(error("bad seat"));
+                        };
+                        if seats[seat].replace(value.to_string()).is_some() {
+                            return Err(error("seat given twice"));
+                        }
+                    }
+                    // unknown tags are fine, like Event
+                    _ => {}
+                }
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let words = rest.split_whitespace().collect::<Vec<_>>();
+
+            if keyword == "Round" {
+                let [_, "Dealer", dealer, "Trump", trump] = words[..] else {
+                    return Err(error("malformed round"));
+                };
+                let Ok(dealer @ 0..4) = dealer.parse() else {
+                    return Err(error("bad dealer"));
+                };
+                record.rounds.push(RoundRecord {
+                    dealer,
+                    trump: match trump {
+                        "S" => Some(Suit::Spades),
+                        "D" => Some(Suit::Diamonds),
+                        "C" => Some(Suit::Clubs),
+                        "H" => Some(Suit::Hearts),
+                        "-" => None,
+                        _ => return Err(error("bad trump")),
+                    },
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            let round = record
+                .rounds
+                .last_mut()
+                .ok_or_else(|| error("expected a round first"))?;
+            match keyword {
+                "Deal" => {
+                    let [seat, cards @ ..] = &words[..] else {
+                        return Err(error("deal without a seat"));
+                    };
+                    let Ok(seat @ 0..4) = seat.parse() else {
+                        return Err(error("bad seat"));
+                    };
+                    // hands are dealt in seat order
+                    if seat != round.hands.len() {
+                        return Err(error(&format!("expected seat {}", round.hands.len())));
+                    }
+                    let hand = text_to_cards(cards).map_err(|e| error(&e))?;
+                    if hand.iter().any(|card| card.player != seat) {
+                        return Err(error("card dealt to another seat"));
+                    }
+                    round.hands.push(hand);
+                }
+                "Calls" => round.calls = text_to_numbers(&words).map_err(|e| error(&e))?,
+                "Trick" => {
+                    if !(1..=4).contains(&words.len()) {
+                        return Err(error("a trick has 1 to 4 cards"));
+                    }
+                    let trick =
+                        text_to_trick(&words, &mut round.joker_calls).map_err(|e| error(&e))?;
+                    round.tricks.push(trick);
+                }
+                "Points" => round.points = text_to_numbers(&words).map_err(|e| error(&e))?,
+                _ => return Err(error("unknown line")),
+            }
+        }
+
+        record.players = seats.into_iter().flatten().collect();
+        if record.players.len() != 4 {
+            return Err(format!("expected 4 seats, found {}", record.players.len()));
+        }
+
+        Ok(record)
+    }
+}
+
+fn cards_to_text(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|card| protocol::encode(card).trim_matches('"').to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn text_to_cards(words: &[&str]) -> Result<Vec<Card>, String> {
+    words
+        .iter()
+        .map(|word| protocol::decode(&format!("\"{word}\"")).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn suit_to_text(suit: Suit) -> &'static str {
+    match suit {
+        Suit::Spades => "S",
+        Suit::Diamonds => "D",
+        Suit::Clubs => "C",
+        Suit::Hearts => "H",
+        Suit::Joker(_) => "",
+    }
+}
+
+fn trick_to_text(trick: &[Card], joker_calls: &[(Card, JokerCall)]) -> String {
+    trick
+        .iter()
+        .map(|card| {
+            let mut text = cards_to_text(&[*card]);
+            match joker_calls.iter().find(|(joker, _)| joker == card) {
+                Some((_, JokerCall::Highest(suit))) => text += &format!("+{}", suit_to_text(*suit)),
+                Some((_, JokerCall::TakeSuit(suit))) => {
+                    text += &format!("-{}", suit_to_text(*suit))
+                }
+                Some((_, JokerCall::Take)) => text += "+",
+                Some((_, JokerCall::Pass)) => text += "-",
+                None => {}
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// the cards of a trick, the jokers' calls are added to `joker_calls`
+fn text_to_trick(
+    words: &[&str],
+    joker_calls: &mut Vec<(Card, JokerCall)>,
+) -> Result<Vec<Card>, String> {
+    let mut trick = vec![];
+
+    for word in words {
+        let (card, call) = word.split_at(word.find(['+', '-']).unwrap_or(word.len()));
+        let card = text_to_cards(&[card])?[0];
+        trick.push(card);
+        if call.is_empty() {
+            continue;
+        }
+
+        let suit = match &call[1..] {
+            "" => None,
+            "S" => Some(Suit::Spades),
+            "D" => Some(Suit::Diamonds),
+            "C" => Some(Suit::Clubs),
+            "H" => Some(Suit::Hearts),
+            _ => return Err(format!("`{word}` has an unknown joker suit")),
+        };
+        if !card.is_joker() {
+            return Err(format!("`{word}` isn't a joker"));
+        }
+        joker_calls.push((
+            card,
+            match (call.starts_with('+'), suit) {
+                (true, Some(suit)) => JokerCall::Highest(suit),
+                (false, Some(suit)) => JokerCall::TakeSuit(suit),
+                (true, None) => JokerCall::Take,
+                (false, None) => JokerCall::Pass,
+            },
+        ));
+    }
+
+    Ok(trick)
+}
+
+fn numbers_to_text(numbers: &[i32]) -> String {
+    numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn text_to_numbers(words: &[&str]) -> Result<Vec<i32>, String> {
+    words
+        .iter()
+        .map(|word| word.parse().map_err(|_| format!("`{word}` isn't a number")))
+        .collect()
+}
+
+pub fn write_record(record: &GameRecord) {
+    let file = format!("records/{}.txt", record.file_stem());
+    match write_data(&file, &record.to_text()) {
+        Ok(path) => info!("game record written to {}", path.display()),
+        Err(err) => warn!("couldn't write the game record: {err}"),
+    }
+}
+
+pub fn read_record(path: &Path) -> Result<GameRecord, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    GameRecord::from_text(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+// the date in PGN's "YYYY.MM.DD" format and the time as "HH:MM:SS.mmm", UTC
+fn now() -> (String, String) {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis()) as i64;
+    let days = millis.div_euclid(86_400_000);
+    let millis = millis.rem_euclid(86_400_000);
+
+    // days since 1970-01-01 to a civil date, from Howard Hinnant's date algorithms
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        format!("{year:04}.{month:02}.{day:02}"),
+        format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            millis / 3_600_000,
+            millis / 60_000 % 60,
+            millis / 1000 % 60,
+            millis % 1000
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+
+    #[test]
+    fn record_round_trips() {
+        let seed = Seed::random();
+        let hands = Deck::shuffled(&seed).deal(2);
+        let record = GameRecord {
+            date: "2026.10.19".to_string(),
+            time: "13:05:42.120".to_string(),
+            players: ["giorgi", "lela", "ilia", "lizi"]
+                .map(String::from)
+                .to_vec(),
+            game_type: GameType::Nines,
+            h_penalty: 500,
+            seed,
+            rounds: vec![RoundRecord {
+                dealer: 3,
+                trump: None,
+                tricks: vec![hands.iter().map(|hand| hand[0]).collect()],
+                hands: hands.to_vec(),
+                calls: vec![1, 0, 0, 1],
+                points: vec![100, -500, 50, 10],
+                ..Default::default()
+            }],
+        };
+
+        let text = record.to_text();
+        let parsed = GameRecord::from_text(&text).unwrap();
+        assert_eq!(parsed.to_text(), text);
+        assert_eq!(parsed.time, "13:05:42.120");
+        assert_eq!(parsed.file_stem(), "20261019-130542120");
+        assert_eq!(parsed.players, record.players);
+        assert_eq!(parsed.game_type, GameType::Nines);
+        assert_eq!(parsed.h_penalty, 500);
+        assert_eq!(parsed.seed, seed);
+        assert_eq!(parsed.rounds[0].hands, record.rounds[0].hands);
+        assert_eq!(parsed.rounds[0].tricks, record.rounds[0].tricks);
+    }
+
+    #[test]
+    fn joker_calls_round_trip() {
+        let words = ["JR0+S", "S91", "JB2-", "C73"];
+        let mut joker_calls = vec![];
+        let trick = text_to_trick(&words, &mut joker_calls).unwrap();
+        assert_eq!(trick.len(), 4);
+        assert_eq!(
+            joker_calls,
+            [
+                (trick[0], JokerCall::Highest(Suit::Spades)),
+                (trick[2], JokerCall::Pass)
+            ]
+        );
+        assert_eq!(trick_to_text(&trick, &joker_calls), words.join(" "));
+        assert!(text_to_trick(&["SA0+S"], &mut joker_calls).is_err());
+        assert!(text_to_trick(&["JR0+X"], &mut joker_calls).is_err());
+    }
+
+    #[test]
+    fn broken_records_are_rejected() {
+        assert!(GameRecord::from_text("Deal 0 SA0").is_err());
+        assert!(GameRecord::from_text("[Seat0 \"a\"]\nRound 1 Dealer x Trump H").is_err());
+        assert!(
+            GameRecord::from_text("[Seat0 \"a\"]\nRound 1 Dealer 0 Trump -\nTrick XX0").is_err()
+        );
+    }
+
+    #[test]
+    fn seats_out_of_range_are_rejected() {
+        let round = |lines: &str| GameRecord::from_text(&format!("[Seat0 \"a\"]\n{lines}"));
+        assert!(round("Round 1 Dealer 4 Trump H").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nDeal").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nDeal x SA0").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nDeal 4 SA4").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nDeal 1 SA1").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nDeal 0 SA1").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nTrick SA9").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nTrick").is_err());
+        assert!(round("Round 1 Dealer 0 Trump H\nTrick SA0 S91 DK2 C73 HA0").is_err());
+        assert!(round("[Seat4 \"b\"]").is_err());
+        assert!(round("[Seatx \"b\"]").is_err());
+        assert!(round("[Seat0 \"b\"]").is_err());
+        // seats can come in any order
+        let seats = "[Seat2 \"c\"]\n[Seat0 \"a\"]\n[Seat3 \"d\"]\n[Seat1 \"b\"]";
+        assert_eq!(
+            GameRecord::from_text(seats).unwrap().players,
+            ["a", "b", "c", "d"]
+        );
+        // only the missing seats are wrong with this one
+        assert_eq!(
+            round("Round 1 Dealer 3 Trump H\nDeal 0 SA0\nDeal 1 S91").err(),
+            Some("expected 4 seats, found 1".to_string())
+        );
+    }
+}
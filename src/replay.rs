@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{card::trick_winner, cleanup, components::*, record::GameRecord, spawn_table};
+
+// steps through a game record, `trick` is how many tricks of `round` have been played
+#[derive(Resource)]
+pub struct Replay {
+    pub record: GameRecord,
+    pub round: usize,
+    pub trick: usize,
+}
+
+impl Replay {
+    pub fn new(record: GameRecord) -> Self {
+        Self {
+            record,
+            round: 0,
+            trick: 0,
+        }
+    }
+
+    // the table as it looked at this point of the game
+    fn game_info(&self) -> GameInfo {
+        let record = &self.record;
+        let round = &record.rounds[self.round];
+        let round_over = self.trick == round.tricks.len();
+
+        let mut game_info = GameInfo {
+            _type: record.game_type,
+            dealer: round.dealer,
+            round: self.round,
+            trump: round.trump,
+            h_penalty: record.h_penalty,
+            seed: record.seed,
+            calls_made: 4,
+            rounds: record.rounds[..=self.round].to_vec(),
+            ..Default::default()
+        };
+
+        for (i, name) in record.players.iter().enumerate() {
+            let rounds = if round_over {
+                &record.rounds[..=self.round]
+            } else {
+                &record.rounds[..self.round]
+            };
+            game_info.players.push_back(Player {
+                name: name.clone(),
+                cards: round.hands.get(i).cloned().unwrap_or_default(),
+                score: rounds.iter().filter_map(|r| r.points.get(i)).sum(),
+                called: round.calls.get(i).copied().unwrap_or_default(),
+                ..Default::default()
+            });
+        }
+
+        for trick in &round.tricks[..self.trick] {
+            let mut placed = VecDeque::new();
+            for card in trick {
+                game_info.players[card.player].cards.retain(|c| c != card);
+                placed.push_front(*card);
+            }
+            let winner = trick_winner(&placed, round.trump, &round.joker_calls).player;
+            game_info.players[winner].taken += 1;
+            game_info.last_took = Some(winner);
+            game_info.cards_placed = placed;
+        }
+
+        game_info
+    }
+}
+
+pub fn enter_replay(mut commands: Commands, mut viewer: ResMut<Viewer>) {
+    // a spectator with every hand face up
+    *viewer = Viewer::default();
+
+    commands.spawn((Text::new("Replay"), ScoresText));
+    commands.spawn((Visibility::Visible, Transform::default(), PlacedCardsNode));
+}
+
+pub fn exit_replay(mut commands: Commands) {
+    commands.insert_resource(Viewer::default());
+    commands.run_system_cached(cleanup);
+}
+
+pub fn replay_controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut replay: ResMut<Replay>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::Menu);
+        return;
+    }
+
+    let rounds = replay.record.rounds.len();
+    if rounds == 0 {
+        return;
+    }
+    let tricks = |replay: &Replay| replay.record.rounds[replay.round].tricks.len();
+
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        if replay.trick < tricks(&replay) {
+            replay.trick += 1;
+        } else if replay.round + 1 < rounds {
+            replay.round += 1;
+            replay.trick = 0;
+        }
+    } else if keys.just_pressed(KeyCode::ArrowLeft) {
+        if replay.trick > 0 {
+            replay.trick -= 1;
+        } else if replay.round > 0 {
+            replay.round -= 1;
+            replay.trick = tricks(&replay);
+        }
+    } else if keys.just_pressed(KeyCode::ArrowDown) && replay.round + 1 < rounds {
+        replay.round += 1;
+        replay.trick = 0;
+    } else if keys.just_pressed(KeyCode::ArrowUp) && replay.round > 0 {
+        replay.round -= 1;
+        replay.trick = 0;
+    }
+}
+
+pub fn show_replay_step(
+    mut commands: Commands,
+    replay: Res<Replay>,
+    mut scores_text: Single<&mut Text, With<ScoresText>>,
+) {
+    if replay.record.rounds.is_empty() {
+        scores_text.0 = "Replay: the record has no rounds\nEsc to leave".to_string();
+        return;
+    }
+
+    let round = &replay.record.rounds[replay.round];
+    scores_text.0 = format!(
+        "Replay ({})\nRound: {}/{}\nTrick: {}/{}\n\nLeft/Right: tricks\nUp/Down: rounds\nEsc: leave",
+        replay.record.date,
+        replay.round + 1,
+        replay.record.rounds.len(),
+        replay.trick,
+        round.tricks.len(),
+    );
+
+    commands.insert_resource(replay.game_info());
+    commands.run_system_cached(spawn_table);
+}
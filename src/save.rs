@@ -0,0 +1,50 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{components::GameInfo, consts::SAVE_FILE};
+
+// everything the game writes goes in the user's data directory, or next to the game if there's none
+pub fn data_path(file: &str) -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("joker"))
+        .unwrap_or_default()
+        .join(file)
+}
+
+// writes `contents` to `file` in the data directory, creating whatever directories are missing
+pub fn write_data(file: &str, contents: &str) -> std::io::Result<PathBuf> {
+    let path = data_path(file);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+pub fn save_game(game_info: &GameInfo) {
+    if let Err(err) = write_data(SAVE_FILE, &serde_json::to_string(game_info).unwrap()) {
+        warn!("couldn't save the game: {err}");
+    }
+}
+
+pub fn load_game() -> Option<GameInfo> {
+    let path = data_path(SAVE_FILE);
+    let save = fs::read_to_string(&path).ok()?;
+
+    match serde_json::from_str(&save) {
+        Ok(game_info) => Some(game_info),
+        Err(err) => {
+            warn!("ignoring broken save {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+pub fn has_save() -> bool {
+    data_path(SAVE_FILE).exists()
+}
+
+pub fn delete_save() {
+    let _ = fs::remove_file(data_path(SAVE_FILE));
+}
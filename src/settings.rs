@@ -0,0 +1,61 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    consts::SETTINGS_FILE,
+    save::{data_path, write_data},
+};
+
+// anything missing from an older settings file gets its default
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // everyone's cards face up, only when there's one human at the table
+    pub show_hands: bool,
+    // cards slide around instead of jumping, `animation_speed` is a multiplier
+    pub animations: bool,
+    pub animation_speed: f32,
+    // seconds a finished trick stays on the table, none waits for a click
+    pub trick_pause: Option<f32>,
+    // the first click or tap on a card only picks it, so a slip doesn't play the wrong one
+    pub tap_to_confirm: bool,
+    // the deck skin and which of its backs, see `skins`
+    pub skin: String,
+    pub back: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            show_hands: true,
+            animations: true,
+            animation_speed: 1.0,
+            trick_pause: Some(1.0),
+            tap_to_confirm: false,
+            skin: "classic".to_string(),
+            back: "R".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let path = data_path(SETTINGS_FILE);
+        let Ok(settings) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&settings).unwrap_or_else(|err| {
+            warn!("ignoring broken settings {}: {err}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = write_data(SETTINGS_FILE, &serde_json::to_string_pretty(self).unwrap()) {
+            warn!("couldn't save the settings: {err}");
+        }
+    }
+}
@@ -0,0 +1,239 @@
+/*
+    Deck skins.
+    A skin is a directory with the 36 faces in `cards/`, named like the ones in `ASSETS`,
+    and one or more backs in `back/`. The classic deck is the one in `assets/` itself,
+    any others go in `assets/skins/<name>/`. Skins with something missing are left out
+    and the menu says what's wrong with them.
+*/
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+use crate::{card::Card, components::*, consts::ASSETS, settings::Settings};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skin {
+    pub name: String,
+    // the asset path `cards/` and `back/` are in
+    pub path: String,
+    // file names in `back/` without the `.png`
+    pub backs: Vec<String>,
+}
+
+impl Skin {
+    fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            path: String::new(),
+            backs: vec!["R".to_string(), "B".to_string()],
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Skins {
+    pub skins: Vec<Skin>,
+    // what's wrong with the skins that were left out
+    pub problems: Vec<String>,
+}
+
+// the backs a skin has, or everything that's missing from it
+pub fn check_skin(dir: &Path) -> Result<Vec<String>, String> {
+    let missing = ASSETS
+        .iter()
+        .map(|code| format!("cards/{code}.png"))
+        .filter(|file| !dir.join(file).is_file())
+        .collect::<Vec<_>>();
+
+    let mut backs = fs::read_dir(dir.join("back"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "png"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect::<Vec<_>>();
+    backs.sort();
+
+    let mut problems = vec![];
+    if !missing.is_empty() {
+        problems.push(format!("is missing {}", missing.join(", ")));
+    }
+    if backs.is_empty() {
+        problems.push("has no backs in back/".to_string());
+    }
+    if problems.is_empty() {
+        Ok(backs)
+    } else {
+        Err(problems.join(" and "))
+    }
+}
+
+impl Skins {
+    // without a directory to look in, like on the web, there's only the classic deck
+    pub fn scan(root: Option<&Path>) -> Self {
+        let Some(root) = root else {
+            return Self {
+                skins: vec![Skin::classic()],
+                problems: vec![],
+            };
+        };
+
+        let mut skins = Self::default();
+        match check_skin(root) {
+            Ok(backs) => skins.skins.push(Skin {
+                backs,
+                ..Skin::classic()
+            }),
+            Err(err) => skins.problems.push(format!("the classic deck {err}")),
+        }
+
+        let mut dirs = fs::read_dir(root.join("skins"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        for dir in dirs {
+            let name = dir.file_name().unwrap().to_string_lossy().to_string();
+            match check_skin(&dir) {
+                Ok(backs) => skins.skins.push(Skin {
+                    path: format!("skins/{name}/"),
+                    name,
+                    backs,
+                }),
+                Err(err) => skins.problems.push(format!("skin `{name}` {err}")),
+            }
+        }
+
+        for problem in &skins.problems {
+            warn!("{problem}");
+        }
+        skins
+    }
+
+    // the skin and back the settings ask for, or the first ones there are
+    pub fn pick(&self, skin: &str, back: &str) -> (Skin, String) {
+        let skin = self
+            .skins
+            .iter()
+            .find(|s| s.name == skin)
+            .or(self.skins.first())
+            .cloned()
+            .unwrap_or_else(Skin::classic);
+        let back = if skin.backs.iter().any(|b| b == back) {
+            back.to_string()
+        } else {
+            skin.backs[0].clone()
+        };
+        (skin, back)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn asset_root() -> Option<PathBuf> {
+    Some(bevy::asset::io::file::FileAssetReader::get_base_path().join("assets"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn asset_root() -> Option<PathBuf> {
+    None
+}
+
+// loads the deck picked in the settings and swaps it into the cards already on the table
+#[allow(clippy::too_many_arguments)]
+pub fn apply_skin(
+    mut assets: ResMut<CardAssets>,
+    mut cards: Query<(&mut Sprite, &Card, Has<PlacedCard>)>,
+    mut loaded: Local<Option<(String, String)>>,
+    skins: Res<Skins>,
+    settings: Res<Settings>,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    asset_server: Res<AssetServer>,
+) {
+    let (skin, back) = skins.pick(&settings.skin, &settings.back);
+    if loaded.as_ref() == Some(&(skin.name.clone(), back.clone())) {
+        return;
+    }
+
+    for code in ASSETS {
+        assets.primary.insert(
+            code.to_string(),
+            asset_server.load(format!("{}cards/{code}.png", skin.path)),
+        );
+    }
+    assets.extra.insert(
+        "back".to_string(),
+        asset_server.load(format!("{}back/{back}.png", skin.path)),
+    );
+
+    for (mut sprite, card, placed) in cards.iter_mut() {
+        let face_up = placed
+            || game_info
+                .players
+                .get(card.player)
+                .is_some_and(|player| viewer.can_see(player, &game_info));
+        sprite.image = if face_up {
+            assets.primary[&card.as_string()].clone()
+        } else {
+            assets.extra["back"].clone()
+        };
+    }
+
+    *loaded = Some((skin.name, back));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incomplete_skins_are_left_out() {
+        let root = std::env::temp_dir().join(format!("joker-skins-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let touch = |file: PathBuf| {
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, []).unwrap();
+        };
+        for dir in [
+            root.clone(),
+            root.join("skins/neon"),
+            root.join("skins/torn"),
+        ] {
+            for code in ASSETS {
+                touch(dir.join(format!("cards/{code}.png")));
+            }
+        }
+        touch(root.join("back/R.png"));
+        touch(root.join("skins/neon/back/blue.png"));
+        touch(root.join("skins/neon/back/notes.txt"));
+        fs::remove_file(root.join("skins/torn/cards/SA.png")).unwrap();
+
+        let skins = Skins::scan(Some(&root));
+        fs::remove_dir_all(&root).unwrap();
+
+        let names = skins
+            .skins
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["classic", "neon"]);
+        assert_eq!(skins.skins[1].path, "skins/neon/");
+        assert_eq!(skins.skins[1].backs, ["blue"]);
+        assert_eq!(
+            skins.problems,
+            ["skin `torn` is missing cards/SA.png and has no backs in back/"]
+        );
+
+        // a skin or back that's gone falls back to the first there is
+        assert_eq!(skins.pick("neon", "R").1, "blue");
+        assert_eq!(skins.pick("torn", "R").0.name, "classic");
+    }
+}
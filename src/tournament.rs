@@ -0,0 +1,173 @@
+/*
+    A match is a number of games between the same four players, with cumulative standings.
+    Seats rotate by one between games, so the player in seat 0 of the first game sits in seat 1
+    of the second and so on.
+
+    In a duplicate match every game is dealt from the same seed, so every seat gets the same
+    cards in every game. With four games each player plays every seat's hands once, and the
+    scores made with the same hands can be compared directly.
+*/
+
+use std::{collections::BTreeMap, fs};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::GameInfo,
+    consts::MATCH_FILE,
+    deck::Seed,
+    save::{data_path, write_data},
+};
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub games_total: usize,
+    pub duplicate: bool,
+    // deals every game of a duplicate match
+    pub seed: Seed,
+    // seat order of the first game
+    pub players: Vec<String>,
+    // who's a bot, by seat of the first game
+    #[serde(default)]
+    pub bots: Vec<bool>,
+    // final scores of every finished game, by seat
+    pub results: Vec<Vec<(String, i32)>>,
+}
+
+impl Match {
+    pub fn new(players: &[&str], bots: &[bool], games_total: usize, duplicate: bool) -> Self {
+        Self {
+            games_total,
+            duplicate,
+            seed: Seed::random(),
+            players: players.iter().map(|name| name.to_string()).collect(),
+            bots: bots.to_vec(),
+            results: vec![],
+        }
+    }
+
+    pub fn load() -> Option<Self> {
+        let path = data_path(MATCH_FILE);
+        let save = fs::read_to_string(&path).ok()?;
+
+        match serde_json::from_str(&save) {
+            Ok(m) => Some(m),
+            Err(err) => {
+                warn!("ignoring broken match {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = write_data(MATCH_FILE, &serde_json::to_string(self).unwrap()) {
+            warn!("couldn't save the match: {err}");
+        }
+    }
+
+    pub fn delete() {
+        let _ = fs::remove_file(data_path(MATCH_FILE));
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.results.len() >= self.games_total
+    }
+
+    // seat order for the next game and who's a bot, matches from before bots were kept have none
+    pub fn seating(&self) -> Vec<(String, bool)> {
+        let mut seats = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), self.bots.get(i).copied().unwrap_or_default()))
+            .collect::<Vec<_>>();
+        let len = seats.len().max(1);
+        seats.rotate_right(self.results.len() % len);
+        seats
+    }
+
+    pub fn add_game(&mut self, game_info: &GameInfo) {
+        self.results.push(
+            game_info
+                .players
+                .iter()
+                .map(|player| (player.name.clone(), player.score))
+                .collect(),
+        );
+    }
+
+    // total score of every player over the finished games, best first
+    pub fn standings(&self) -> Vec<(String, i32)> {
+        let mut totals = BTreeMap::new();
+        for name in &self.players {
+            totals.insert(name.clone(), 0);
+        }
+        for (name, score) in self.results.iter().flatten() {
+            *totals.entry(name.clone()).or_default() += score;
+        }
+
+        let mut standings = totals.into_iter().collect::<Vec<_>>();
+        standings.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        standings
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{} match, game {}/{}\n",
+            if self.duplicate { "Duplicate" } else { "Free" },
+            (self.results.len() + 1).min(self.games_total),
+            self.games_total
+        );
+        for (i, (name, score)) in self.standings().iter().enumerate() {
+            text += &format!("{}. {name} {:.2}\n", i + 1, *score as f32 / 100.0);
+        }
+
+        // the same hands side by side, who made the most of them
+        if self.duplicate && !self.results.is_empty() {
+            for seat in 0..self.players.len() {
+                text += &format!("seat {} hands:", seat + 1);
+                for (name, score) in self.results.iter().filter_map(|game| game.get(seat)) {
+                    text += &format!(" {name} {:.2}", *score as f32 / 100.0);
+                }
+                text += "\n";
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Player;
+
+    #[test]
+    fn seats_rotate_and_scores_add_up() {
+        let mut m = Match::new(&["a", "b", "c", "d"], &[true, false, false, true], 2, true);
+        let seating = |m: &Match| {
+            m.seating()
+                .into_iter()
+                .map(|(name, bot)| format!("{name}{}", if bot { " bot" } else { "" }))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(seating(&m), ["a bot", "b", "c", "d bot"]);
+
+        let mut game_info = GameInfo::default();
+        for (name, score) in [("a", 100), ("b", 300), ("c", -200), ("d", 0)] {
+            game_info.players.push_back(Player {
+                name: name.to_string(),
+                score,
+                ..Default::default()
+            });
+        }
+        m.add_game(&game_info);
+        assert_eq!(seating(&m), ["d bot", "a bot", "b", "c"]);
+        assert!(!m.is_over());
+
+        m.add_game(&game_info);
+        assert!(m.is_over());
+        assert_eq!(m.standings()[0], ("b".to_string(), 600));
+        assert_eq!(m.standings()[3], ("c".to_string(), -400));
+    }
+}
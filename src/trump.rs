@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+
+use crate::{
+    button,
+    card::Suit,
+    components::*,
+    consts::{ASSETS, CSH, CSW},
+    spawn_table,
+    undo::UndoHistory,
+};
+
+fn suit_color(suit: Suit) -> Color {
+    match suit {
+        Suit::Diamonds | Suit::Hearts => Color::srgb(0.9, 0.25, 0.25),
+        _ => Color::WHITE,
+    }
+}
+
+fn card_image(image: Handle<Image>) -> impl Bundle {
+    (
+        ImageNode::new(image),
+        Node {
+            width: Val::Px(CSW),
+            height: Val::Px(CSH),
+            margin: UiRect::all(Val::Px(4.0)),
+            ..Default::default()
+        },
+        Pickable::IGNORE,
+    )
+}
+
+// the chooser for the player after the dealer when the whole deck is dealt,
+// they only get to see their first three cards before picking
+pub fn update_trump_chooser(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    viewer: Res<Viewer>,
+    assets: Res<CardAssets>,
+    chooser: Query<Entity, With<TrumpChooser>>,
+) {
+    for entity in chooser {
+        commands.entity(entity).despawn();
+    }
+
+    if !game_info.picking_trump || !viewer.has_turn(&game_info) {
+        return;
+    }
+    let seat = game_info.current_player();
+    let player = &game_info.players[seat];
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            TrumpChooser,
+        ))
+        .with_children(|chooser| {
+            chooser.spawn((
+                Text::new(format!(
+                    "{}, pick the trump\nThese are your first three cards",
+                    player.name
+                )),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+            chooser.spawn(Node::default()).with_children(|cards| {
+                for card in game_info.first_cards(seat) {
+                    cards.spawn(card_image(assets.primary[&card.as_string()].clone()));
+                }
+            });
+            chooser.spawn(Node::default()).with_children(|buttons| {
+                // the number keys pick them too
+                for (i, trump) in Suit::ALL.map(Some).into_iter().chain([None]).enumerate() {
+                    let label = match trump {
+                        Some(suit) => format!("{}: {suit:?}", i + 1),
+                        None => "0: No trump".to_string(),
+                    };
+                    buttons.spawn(button(&label)).observe(
+                        move |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                            commands.run_system_cached_with(pick_trump, trump);
+                        },
+                    );
+                }
+            });
+        });
+}
+
+pub fn pick_trump(
+    In(trump): In<Option<Suit>>,
+    mut commands: Commands,
+    mut game_info: ResMut<GameInfo>,
+    mut history: ResMut<UndoHistory>,
+) {
+    if !game_info.picking_trump {
+        return;
+    }
+    history.push(&game_info);
+    game_info.pick_trump(trump);
+    commands.run_system_cached(spawn_table);
+}
+
+// the trump in the top right corner, the flipped card or the suit, as `GameInfo::trump` has it
+pub fn update_trump_widget(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    assets: Res<CardAssets>,
+    widget: Query<Entity, With<TrumpWidget>>,
+) {
+    for entity in widget {
+        commands.entity(entity).despawn();
+    }
+
+    if game_info.players.is_empty() {
+        return;
+    }
+    let picker = &game_info.players[(game_info.dealer + 1) % 4].name;
+
+    commands
+        .spawn((
+            Node {
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Pickable::IGNORE,
+            TrumpWidget,
+        ))
+        .with_children(|widget| {
+            widget.spawn((Text::new("Trump"), Pickable::IGNORE));
+
+            if game_info.picking_trump {
+                widget.spawn((Text::new(format!("{picker} is picking")), Pickable::IGNORE));
+            } else if let Some(card) = game_info.flipped {
+                widget.spawn(card_image(assets.primary[&card.as_string()].clone()));
+                if card.is_joker() {
+                    widget.spawn((Text::new("none"), Pickable::IGNORE));
+                }
+            } else {
+                let (text, color) = match game_info.trump {
+                    Some(suit) => (format!("{suit:?}"), suit_color(suit)),
+                    None => ("none".to_string(), Color::WHITE),
+                };
+                widget.spawn((
+                    Text::new(text),
+                    TextFont::from_font_size(32.0),
+                    TextColor(color),
+                    Pickable::IGNORE,
+                ));
+                // the trump's only picked when the whole deck is dealt
+                if game_info.cards_per_player() * 4 == ASSETS.len() {
+                    widget.spawn((
+                        Text::new(format!("picked by {picker}")),
+                        TextFont::from_font_size(14.0),
+                        Pickable::IGNORE,
+                    ));
+                }
+            }
+        });
+}
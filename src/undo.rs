@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use crate::{components::GameInfo, save::save_game, spawn_table};
+
+// snapshots of the game from before every move this round, cleared when a round starts.
+// bots' moves are in it too, undo and redo step over them to where it's a person's turn
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    undo: Vec<GameInfo>,
+    redo: Vec<GameInfo>,
+}
+
+fn human_turn(game_info: &GameInfo) -> bool {
+    !game_info.players[game_info.current_player()].bot
+}
+
+impl UndoHistory {
+    // call before changing `game_info`, a new move throws away anything that was undone
+    pub fn push(&mut self, game_info: &GameInfo) {
+        self.undo.push(game_info.clone());
+        self.redo.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    // back to before the last move a person made, with the bots' moves after it,
+    // false when nobody's made one this round
+    fn step_back(&mut self, game_info: &mut GameInfo) -> bool {
+        let Some(target) = self.undo.iter().rposition(human_turn) else {
+            return false;
+        };
+        while self.undo.len() > target {
+            let previous = self.undo.pop().unwrap();
+            self.redo.push(std::mem::replace(game_info, previous));
+        }
+        true
+    }
+
+    // forward over the undone move and the bots' moves after it
+    fn step_forward(&mut self, game_info: &mut GameInfo) -> bool {
+        let mut moved = false;
+        while let Some(next) = self.redo.pop() {
+            self.undo.push(std::mem::replace(game_info, next));
+            moved = true;
+            if human_turn(game_info) {
+                break;
+            }
+        }
+        moved
+    }
+}
+
+pub fn undo(
+    mut commands: Commands,
+    mut history: ResMut<UndoHistory>,
+    mut game_info: ResMut<GameInfo>,
+) {
+    if history.step_back(&mut game_info) {
+        save_game(&game_info);
+        commands.run_system_cached(spawn_table);
+    }
+}
+
+pub fn redo(
+    mut commands: Commands,
+    mut history: ResMut<UndoHistory>,
+    mut game_info: ResMut<GameInfo>,
+) {
+    if history.step_forward(&mut game_info) {
+        save_game(&game_info);
+        commands.run_system_cached(spawn_table);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Player;
+
+    #[test]
+    fn undo_skips_the_bots_moves() {
+        let mut game_info = GameInfo {
+            dealer: 3,
+            ..Default::default()
+        };
+        for seat in 0..4 {
+            game_info.players.push_back(Player {
+                bot: seat != 1,
+                ..Default::default()
+            });
+        }
+
+        // the bot in seat 0 calls, then the person in seat 1 and the bots after them
+        let mut history = UndoHistory::default();
+        for called in 0..4 {
+            history.push(&game_info);
+            game_info.call(called);
+        }
+        assert!(!game_info.calling());
+
+        assert!(history.step_back(&mut game_info));
+        assert_eq!(game_info.calls_made, 1);
+        assert_eq!(game_info.current_player(), 1);
+        // nothing the person did is left to undo
+        assert!(!history.step_back(&mut game_info));
+
+        assert!(history.step_forward(&mut game_info));
+        assert_eq!(game_info.calls_made, 4);
+        assert!(!history.step_forward(&mut game_info));
+    }
+}